@@ -0,0 +1,36 @@
+//! Times decoding a looped stream of the pure parts of a message.
+//!
+//! Each pass over the message misses a third of the parts, such that the
+//! decoder completes on the second pass, skipping the parts received before.
+//!
+//! ```text
+//! cargo run --release --example loop -- [message length] [max fragment length]
+//! ```
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let message_length = args.next().map_or(100_000, |arg| arg.parse().unwrap());
+    let max_fragment_length = args.next().map_or(10, |arg| arg.parse().unwrap());
+    let message: Vec<u8> = (0..=u8::MAX).cycle().take(message_length).collect();
+    let mut encoder = ur::Encoder::new(&message, max_fragment_length, "bytes").unwrap();
+    let parts: Vec<String> = (0..encoder.fragment_count())
+        .map(|_| encoder.next_part().unwrap())
+        .collect();
+    let start = std::time::Instant::now();
+    let mut decoder = ur::Decoder::default();
+    for pass in 0..3 {
+        for (index, part) in parts.iter().enumerate() {
+            if (index + pass) % 3 != 0 {
+                decoder.receive(part).unwrap();
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(decoder.message().unwrap(), Some(message));
+    println!(
+        "{} parts, {} duplicates, decoded in {:?}",
+        3 * parts.len(),
+        decoder.statistics().duplicate_parts,
+        elapsed
+    );
+}
@@ -0,0 +1,38 @@
+//! Compares the choices of the maximum fragment length for transferring a file.
+//!
+//! ```text
+//! cargo run --release --example plan -- <file> [ur type]
+//! ```
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().expect("usage: plan <file> [ur type]");
+    let ur_type = args.next().unwrap_or_else(|| String::from("bytes"));
+    let message = std::fs::read(path).unwrap();
+    println!(
+        "{:>9} {:>10} {:>10} {:>9} {:>14} {:>14}",
+        "fragment", "part chars", "QR version", "one pass", "frames 10%", "frames 30%"
+    );
+    for max_fragment_length in [10, 25, 50, 100, 200, 400, 800, 1600] {
+        let estimate =
+            ur::estimate::transfer_estimate(message.len(), max_fragment_length, 1.0, 1.0).unwrap();
+        let part_length = ur::transfer::max_part_length(&ur_type, max_fragment_length);
+        let version = ur::estimate::qr_version(part_length)
+            .map_or_else(|| String::from("-"), |version| version.to_string());
+        let frames = |loss: f32| {
+            ur::estimate::simulate_frames(&message, max_fragment_length, 1.0 - loss, 10).unwrap()
+        };
+        println!(
+            "{:>9} {:>10} {:>10} {:>9} {:>14.1} {:>14.1}",
+            max_fragment_length,
+            part_length,
+            version,
+            estimate.fragment_count,
+            frames(0.1),
+            frames(0.3)
+        );
+        if estimate.fragment_count == 1 {
+            break;
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::io::Write;
 
 fn main() {
     let mut encoder =
-        ur::Encoder::new(std::env::args().last().unwrap().as_bytes(), 5, "bytes").unwrap();
+        ur::Encoder::new(std::env::args().next_back().unwrap().as_bytes(), 5, "bytes").unwrap();
     let mut stdout = std::io::stdout();
     loop {
         let ur = encoder.next_part().unwrap();
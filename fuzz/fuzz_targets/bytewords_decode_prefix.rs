@@ -0,0 +1,21 @@
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            if let Ok(encoded) = std::str::from_utf8(data) {
+                for style in [
+                    ur::bytewords::Style::Standard,
+                    ur::bytewords::Style::Uri,
+                    ur::bytewords::Style::Minimal,
+                ] {
+                    let (prefix, result) = ur::bytewords::decode_prefix(encoded, &style);
+                    match ur::bytewords::decode(encoded, &style) {
+                        Ok(decoded) => assert_eq!(decoded, prefix),
+                        Err(e) => assert_eq!(result.unwrap_err(), e),
+                    }
+                }
+            }
+        });
+    }
+}
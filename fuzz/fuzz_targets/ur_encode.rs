@@ -3,8 +3,15 @@ use honggfuzz::fuzz;
 fn main() {
     loop {
         fuzz!(|data: &[u8]| {
-            let max_length = 1 + *data.first().unwrap() as usize;
-            let mut encoder = ur::Encoder::new(data, max_length, "bytes").unwrap();
+            let max_length = 1 + data.first().copied().unwrap_or_default() as usize;
+            let mut encoder = match ur::Encoder::new(data, max_length, "bytes") {
+                Ok(encoder) => encoder,
+                Err(e) => {
+                    assert!(data.is_empty());
+                    assert!(e.is::<ur::fountain::EmptyMessage>());
+                    return;
+                }
+            };
             let mut decoder = ur::Decoder::default();
             for _ in 0..encoder.fragment_count() {
                 let part = encoder.next_part().unwrap();
@@ -0,0 +1,28 @@
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            if data.len() < 3 {
+                return;
+            }
+            let (header, data) = data.split_at(3);
+            let max_length = 1 + *header.first().unwrap() as usize;
+            let extra_parts = *header.get(1).unwrap() as usize;
+            let message_length = (1 + *header.get(2).unwrap() as usize).min(data.len());
+            let (message, mutations) = data.split_at(message_length);
+            let mut stream = match ur::testing::Stream::new(message, max_length, extra_parts) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    assert!(message.is_empty());
+                    assert!(e.is::<ur::fountain::EmptyMessage>());
+                    return;
+                }
+            };
+            for mutation in ur::testing::Mutation::from_bytes(mutations) {
+                stream.apply(mutation);
+            }
+            stream.check();
+        });
+    }
+}
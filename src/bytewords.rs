@@ -47,13 +47,28 @@ pub enum Style {
     Minimal,
 }
 
-/// The two different errors that can be returned when decoding.
+/// The different errors that can be returned when decoding.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     /// Usually indicates a wrong encoding [`Style`] was passed.
     InvalidWord,
     /// The CRC32 checksum doesn't validate.
     InvalidChecksum,
+    /// The input ends in the middle of a word or is too short to even
+    /// contain the checksum, which indicates it has been cut off.
+    Truncated {
+        /// A lower bound on the number of characters missing from the input.
+        missing_chars_at_least: usize,
+    },
+    /// No single changed word makes the input decode, see [`correct_single_error`].
+    Uncorrectable,
+    /// More than one single changed word makes the input decode, see [`correct_single_error`].
+    Ambiguous,
+    /// The input has more words than allowed to attempt a correction, see [`correct_single_error`].
+    TooLong {
+        /// The maximum number of words.
+        max_words: usize,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -61,12 +76,50 @@ impl std::fmt::Display for Error {
         match self {
             Error::InvalidWord => write!(f, "invalid word"),
             Error::InvalidChecksum => write!(f, "invalid checksum"),
+            Error::Truncated {
+                missing_chars_at_least,
+            } => write!(
+                f,
+                "truncated input, at least {missing_chars_at_least} characters missing"
+            ),
+            Error::Uncorrectable => write!(f, "no single-word correction found"),
+            Error::Ambiguous => write!(f, "multiple single-word corrections found"),
+            Error::TooLong { max_words } => {
+                write!(f, "too long to correct, at most {max_words} words allowed")
+            }
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::bytewords::{decode, Style};
+    /// assert_eq!(
+    ///     decode("able tied also webs lung", &Style::Minimal)
+    ///         .unwrap_err()
+    ///         .code(),
+    ///     ur::ErrorCode(101)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(match self {
+            Error::InvalidWord => 101,
+            Error::InvalidChecksum => 102,
+            Error::Truncated { .. } => 103,
+            Error::Uncorrectable => 104,
+            Error::Ambiguous => 105,
+            Error::TooLong { .. } => 106,
+        })
+    }
+}
+
 /// Deocdes a `bytewords`-encoded String back into a byte payload. The encoding
 /// must contain a four-byte checksum.
 ///
@@ -89,48 +142,252 @@ impl std::error::Error for Error {}
 /// # Errors
 ///
 /// If the encoded string contains unrecognized words, is inconsistent with
-/// the provided `style`, or contains an invalid checksum, an error will be
-/// returned.
+/// the provided `style`, has been cut off, or contains an invalid checksum,
+/// an error will be returned.
 pub fn decode(encoded: &str, style: &Style) -> Result<Vec<u8>, Error> {
-    let separator = match style {
-        Style::Standard => " ",
-        Style::Uri => "-",
-        Style::Minimal => return decode_minimal(encoded),
-    };
-    decode_from_index(&mut encoded.split(separator), &crate::constants::WORD_IDXS)
+    let (decoded, result) = decode_prefix(encoded, style);
+    result.map(|()| decoded)
 }
 
-fn decode_minimal(encoded: &str) -> Result<Vec<u8>, Error> {
-    decode_from_index(
-        &mut (0..encoded.len())
-            .step_by(2)
-            .map(|idx| encoded.get(idx..idx + 2).unwrap()),
-        &crate::constants::MINIMAL_IDXS,
-    )
+/// Decodes as much of a `bytewords`-encoded String as possible, which is
+/// useful to show the salvageable part of a damaged input.
+///
+/// Returns the bytes of all words preceding the first error, together with
+/// the outcome of the decoding. If the input decodes successfully or only the
+/// checksum doesn't validate, the trailing four checksum bytes are stripped.
+///
+/// # Examples
+///
+/// ```
+/// use ur::bytewords::{decode_prefix, Error, Style};
+/// assert_eq!(
+///     decode_prefix("aeadaolazmjendeoti", &Style::Minimal),
+///     (vec![0, 1, 2, 128, 255], Ok(()))
+/// );
+/// // a corrupted word
+/// assert_eq!(
+///     decode_prefix("aeadaoxxzmjendeoti", &Style::Minimal),
+///     (vec![0, 1, 2], Err(Error::InvalidWord))
+/// );
+/// // an input which has been cut off in the middle of a word
+/// assert_eq!(
+///     decode_prefix("aeadaolazmjendeot", &Style::Minimal),
+///     (
+///         vec![0, 1, 2, 128, 255, 107, 155, 51],
+///         Err(Error::Truncated {
+///             missing_chars_at_least: 1
+///         })
+///     )
+/// );
+/// ```
+pub fn decode_prefix(encoded: &str, style: &Style) -> (Vec<u8>, Result<(), Error>) {
+    let (mut decoded, result) = decode_words(encoded, style);
+    if result.is_err() {
+        return (decoded, result);
+    }
+    let checksum = decoded.split_off(decoded.len() - 4);
+    if crate::crc32().checksum(&decoded).to_be_bytes() == checksum.as_slice() {
+        (decoded, Ok(()))
+    } else {
+        (decoded, Err(Error::InvalidChecksum))
+    }
+}
+
+// Decodes all words including the checksum, which is guaranteed to be
+// present if no error is returned.
+fn decode_words(encoded: &str, style: &Style) -> (Vec<u8>, Result<(), Error>) {
+    let (separator, word_length, indexes) = match style {
+        Style::Standard => (Some(' '), 4, &*crate::constants::WORD_IDXS),
+        Style::Uri => (Some('-'), 4, &*crate::constants::WORD_IDXS),
+        Style::Minimal => (None, 2, &*crate::constants::MINIMAL_IDXS),
+    };
+    let mut words = match separator {
+        Some(separator) => encoded.split(separator).collect(),
+        None => split_minimal(encoded),
+    };
+    let partial = match words.last() {
+        Some(word) if word.len() < word_length && word.chars().all(|c| c.is_ascii_lowercase()) => {
+            words.pop()
+        }
+        _ => None,
+    };
+    let mut decoded = Vec::with_capacity(words.len());
+    for word in words {
+        match indexes.get(word) {
+            Some(&byte) => decoded.push(byte),
+            None => return (decoded, Err(Error::InvalidWord)),
+        }
+    }
+    let minimum_length = 4 * word_length + 3 * usize::from(separator.is_some());
+    let missing = minimum_length.saturating_sub(encoded.len());
+    match partial {
+        Some(word) => (
+            decoded,
+            Err(Error::Truncated {
+                missing_chars_at_least: missing.max(word_length - word.len()),
+            }),
+        ),
+        None if decoded.len() < 4 => (
+            decoded,
+            Err(Error::Truncated {
+                missing_chars_at_least: missing,
+            }),
+        ),
+        None => (decoded, Ok(())),
+    }
 }
 
-fn decode_from_index(
-    keys: &mut dyn Iterator<Item = &str>,
-    indexes: &std::collections::HashMap<&'static str, u8>,
-) -> Result<Vec<u8>, Error> {
-    strip_checksum(
-        &keys
-            .map(|k| indexes.get(k).copied())
-            .collect::<Option<Vec<_>>>()
-            .ok_or(Error::InvalidWord)?,
-    )
+/// A single word changed by [`correct_single_error`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Correction {
+    /// The decoded payload after the correction.
+    pub data: Vec<u8>,
+    /// The index of the changed word, counting the checksum words as well.
+    pub position: usize,
+    /// The word as found in the input.
+    pub original: String,
+    /// The word replacing it.
+    pub corrected: &'static str,
 }
 
-fn strip_checksum(data: &[u8]) -> Result<Vec<u8>, Error> {
-    if data.len() < 4 {
-        return Err(Error::InvalidChecksum);
+/// Attempts to correct a single misread or mistyped word of a `bytewords`-encoded
+/// String, using the checksum to find the word and its replacement.
+///
+/// Returns `None` if the input decodes without correction. As up to 256 replacements
+/// are tried per word, the input length is bounded by `max_words`. Since a wrong
+/// correction is possible in principle, the result should be confirmed by the user.
+///
+/// # Examples
+///
+/// ```
+/// use ur::bytewords::{correct_single_error, Correction, Style};
+/// assert_eq!(
+///     correct_single_error("able acid also lava zero jade need echo taxi", &Style::Standard, 100),
+///     Ok(Some(Correction {
+///         data: vec![0, 1, 2, 128, 255],
+///         position: 4,
+///         original: "zero".to_string(),
+///         corrected: "zoom",
+///     }))
+/// );
+/// assert_eq!(correct_single_error("aeadaolazmjendeoti", &Style::Minimal, 100), Ok(None));
+/// ```
+///
+/// # Errors
+///
+/// If the input has more than `max_words` words, is too short to contain the
+/// checksum, or if not exactly one single changed word makes it decode,
+/// an error will be returned.
+pub fn correct_single_error(
+    encoded: &str,
+    style: &Style,
+    max_words: usize,
+) -> Result<Option<Correction>, Error> {
+    let (words, indexes, table) = match style {
+        Style::Standard => (
+            encoded.split(' ').collect(),
+            &*crate::constants::WORD_IDXS,
+            &crate::constants::WORDS,
+        ),
+        Style::Uri => (
+            encoded.split('-').collect(),
+            &*crate::constants::WORD_IDXS,
+            &crate::constants::WORDS,
+        ),
+        Style::Minimal => (
+            split_minimal(encoded),
+            &*crate::constants::MINIMAL_IDXS,
+            &crate::constants::MINIMALS,
+        ),
+    };
+    if words.len() > max_words {
+        return Err(Error::TooLong { max_words });
     }
-    let (payload, checksum) = data.split_at(data.len() - 4);
-    if crate::crc32().checksum(payload).to_be_bytes() == checksum {
-        Ok(payload.to_vec())
-    } else {
-        Err(Error::InvalidChecksum)
+    if decode(encoded, style).is_ok() {
+        return Ok(None);
+    }
+    if words.len() < 4 {
+        return Err(Error::Uncorrectable);
+    }
+    let bytes: Vec<Option<u8>> = words
+        .iter()
+        .map(|word| indexes.get(word).copied())
+        .collect();
+    let mut invalid = bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| byte.is_none())
+        .map(|(position, _)| position);
+    let positions: Vec<usize> = match (invalid.next(), invalid.next()) {
+        (None, _) => (0..bytes.len()).collect(),
+        (Some(position), None) => vec![position],
+        (Some(_), Some(_)) => return Err(Error::Uncorrectable),
+    };
+    let mut candidate: Vec<u8> = bytes.iter().map(|byte| byte.unwrap_or_default()).collect();
+    let mut correction = None;
+    for position in positions {
+        let original = bytes.get(position).copied().flatten();
+        for value in (0..=u8::MAX).filter(|&value| Some(value) != original) {
+            if let Some(byte) = candidate.get_mut(position) {
+                *byte = value;
+            }
+            let (data, checksum) = candidate.split_at(candidate.len() - 4);
+            if crate::crc32().checksum(data).to_be_bytes() == checksum
+                && correction.replace((position, value)).is_some()
+            {
+                return Err(Error::Ambiguous);
+            }
+        }
+        if let Some(byte) = candidate.get_mut(position) {
+            *byte = original.unwrap_or_default();
+        }
+    }
+    let (position, value) = correction.ok_or(Error::Uncorrectable)?;
+    if let Some(byte) = candidate.get_mut(position) {
+        *byte = value;
+    }
+    candidate.truncate(candidate.len() - 4);
+    Ok(Some(Correction {
+        data: candidate,
+        position,
+        original: words.get(position).copied().unwrap_or_default().to_string(),
+        corrected: table.get(usize::from(value)).copied().unwrap_or_default(),
+    }))
+}
+
+// Decodes at most the given number of leading bytes of a minimal encoding,
+// without verifying the checksum.
+pub(crate) fn decode_minimal_head(encoded: &str, length: usize) -> Result<Vec<u8>, Error> {
+    encoded
+        .as_bytes()
+        .chunks(2)
+        .take(length)
+        .map(|word| match std::str::from_utf8(word) {
+            Ok(word) if word.len() < 2 => Err(Error::Truncated {
+                missing_chars_at_least: 1,
+            }),
+            Ok(word) => crate::constants::MINIMAL_IDXS
+                .get(word)
+                .copied()
+                .ok_or(Error::InvalidWord),
+            Err(_) => Err(Error::InvalidWord),
+        })
+        .collect()
+}
+
+fn split_minimal(encoded: &str) -> Vec<&str> {
+    let mut words = Vec::with_capacity(encoded.len() / 2);
+    let mut remaining = encoded;
+    while !remaining.is_empty() {
+        let end = remaining
+            .char_indices()
+            .nth(2)
+            .map_or(remaining.len(), |(idx, _)| idx);
+        let (word, rest) = remaining.split_at(end);
+        words.push(word);
+        remaining = rest;
     }
+    words
 }
 
 /// Encodes a byte payload into a `bytewords` encoded String.
@@ -164,10 +421,117 @@ pub fn encode(data: &[u8], style: &Style) -> String {
     words.join(separator)
 }
 
+/// Returns the length of the encoding of a payload of the given length
+/// in the given style, including the checksum.
+///
+/// # Examples
+///
+/// ```
+/// use ur::bytewords::{encode, encoded_len, Style};
+/// const LENGTH: usize = encoded_len(10, &Style::Minimal);
+/// assert_eq!(LENGTH, encode(&[0; 10], &Style::Minimal).len());
+/// ```
+#[must_use]
+pub const fn encoded_len(data_length: usize, style: &Style) -> usize {
+    let words = data_length + 4;
+    match style {
+        Style::Standard | Style::Uri => 5 * words - 1,
+        Style::Minimal => 2 * words,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // evaluated at compile time
+    const _: () = assert!(encoded_len(0, &Style::Standard) == 19);
+    const _: () = assert!(encoded_len(10, &Style::Uri) == 69);
+    const _: () = assert!(encoded_len(10, &Style::Minimal) == 28);
+
+    #[test]
+    fn test_encoded_len() {
+        for length in 0..100 {
+            let data = vec![0xab; length];
+            for style in [Style::Standard, Style::Uri, Style::Minimal] {
+                assert_eq!(encoded_len(length, &style), encode(&data, &style).len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_correct_single_error() {
+        let data = "Some bytes".as_bytes();
+        for style in [Style::Standard, Style::Uri, Style::Minimal] {
+            let encoded = encode(data, &style);
+            let word_length = if style == Style::Minimal { 2 } else { 5 };
+            for position in 0..data.len() + 4 {
+                let start = position * word_length;
+                // a misread word, which is another valid word
+                let mut corrupted = encoded.clone();
+                let replacement = if style == Style::Minimal {
+                    "ae"
+                } else {
+                    "able"
+                };
+                let original = &encoded[start..start + replacement.len()];
+                if original == replacement {
+                    continue;
+                }
+                corrupted.replace_range(start..start + replacement.len(), replacement);
+                let correction = correct_single_error(&corrupted, &style, 100)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(correction.data, data);
+                assert_eq!(correction.position, position);
+                assert_eq!(correction.original, replacement);
+                assert_eq!(correction.corrected, original);
+                // a mistyped word, which is invalid
+                corrupted.replace_range(start..=start, "x");
+                let correction = correct_single_error(&corrupted, &style, 100)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(correction.data, data);
+                assert_eq!(correction.position, position);
+                assert_eq!(correction.corrected, original);
+            }
+        }
+    }
+
+    #[test]
+    fn test_correct_single_error_failures() {
+        // two errors
+        assert_eq!(
+            correct_single_error(
+                "able acid also lava zero jade need echo wolf",
+                &Style::Standard,
+                100
+            ),
+            Err(Error::Uncorrectable)
+        );
+        assert_eq!(
+            correct_single_error("aeadaoxxzmjendeoxx", &Style::Minimal, 100),
+            Err(Error::Uncorrectable)
+        );
+        // too short to contain a checksum
+        assert_eq!(
+            correct_single_error("able acid", &Style::Standard, 100),
+            Err(Error::Uncorrectable)
+        );
+        assert_eq!(
+            correct_single_error("aeadaolazmjendeoti", &Style::Minimal, 8),
+            Err(Error::TooLong { max_words: 8 })
+        );
+        assert_eq!(
+            Error::TooLong { max_words: 8 }.to_string(),
+            "too long to correct, at most 8 words allowed"
+        );
+        assert_eq!(
+            Error::Ambiguous.to_string(),
+            "multiple single-word corrections found"
+        );
+    }
+
     #[test]
     fn test_crc() {
         assert_eq!(crate::crc32().checksum(b"Hello, world!"), 0xebe6_c6e6);
@@ -228,12 +592,148 @@ mod tests {
         // too short
         assert_eq!(
             decode("wolf", &Style::Standard).unwrap_err(),
-            Error::InvalidChecksum
+            Error::Truncated {
+                missing_chars_at_least: 15
+            }
         );
         assert_eq!(
             decode("", &Style::Standard).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 19
+            }
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        let input = vec![0, 1, 2, 128, 255];
+        for style in [Style::Standard, Style::Uri, Style::Minimal] {
+            let encoded = encode(&input, &style);
+            let word_length = if style == Style::Minimal { 2 } else { 4 };
+            // cut off within the last word
+            for cut in 1..word_length {
+                assert_eq!(
+                    decode(encoded.get(..encoded.len() - cut).unwrap(), &style).unwrap_err(),
+                    Error::Truncated {
+                        missing_chars_at_least: cut
+                    }
+                );
+            }
+            // a trailing separator indicates a missing word
+            if style != Style::Minimal {
+                assert_eq!(
+                    decode(encoded.get(..encoded.len() - word_length).unwrap(), &style)
+                        .unwrap_err(),
+                    Error::Truncated {
+                        missing_chars_at_least: word_length
+                    }
+                );
+            }
+            // cutting off a whole word can't be told apart from a corrupted checksum
+            let without_last_word = encoded
+                .get(..encoded.len() - word_length - usize::from(style != Style::Minimal))
+                .unwrap();
+            assert_eq!(
+                decode(without_last_word, &style).unwrap_err(),
+                Error::InvalidChecksum
+            );
+        }
+        // too short to contain the checksum
+        assert_eq!(
+            decode("aetdaows", &Style::Minimal).unwrap_err(),
+            Error::InvalidChecksum
+        );
+        assert_eq!(
+            decode("aetdaow", &Style::Minimal).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 1
+            }
+        );
+        assert_eq!(
+            decode("aetdao", &Style::Minimal).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 2
+            }
+        );
+        assert_eq!(
+            decode("", &Style::Minimal).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 8
+            }
+        );
+        assert_eq!(
+            decode("able tied al", &Style::Standard).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 7
+            }
+        );
+        assert_eq!(
+            decode("able-tied-also-webs-", &Style::Uri).unwrap_err(),
+            Error::Truncated {
+                missing_chars_at_least: 4
+            }
+        );
+        // invalid words take precedence
+        assert_eq!(
+            decode("able tiex al", &Style::Standard).unwrap_err(),
+            Error::InvalidWord
+        );
+        assert_eq!(
+            decode("aetdaOwslg", &Style::Minimal).unwrap_err(),
             Error::InvalidWord
         );
+        // non-ASCII input must not panic
+        assert_eq!(
+            decode("aeädaowslg", &Style::Minimal).unwrap_err(),
+            Error::InvalidWord
+        );
+        assert_eq!(
+            decode("aetdaowslä", &Style::Minimal).unwrap_err(),
+            Error::InvalidWord
+        );
+    }
+
+    #[test]
+    fn test_decode_prefix() {
+        let input = vec![0, 1, 2, 128, 255];
+        for style in [Style::Standard, Style::Uri, Style::Minimal] {
+            let encoded = encode(&input, &style);
+            assert_eq!(decode_prefix(&encoded, &style), (input.clone(), Ok(())));
+            let word_length = if style == Style::Minimal { 2 } else { 4 };
+            // a truncated input yields all whole words, including the partial checksum
+            let (prefix, result) = decode_prefix(encoded.get(..encoded.len() - 1).unwrap(), &style);
+            let checksum = crate::crc32().checksum(&input).to_be_bytes();
+            assert_eq!(prefix, [&input, checksum.get(..3).unwrap()].concat());
+            assert_eq!(
+                result,
+                Err(Error::Truncated {
+                    missing_chars_at_least: 1
+                })
+            );
+            // a corrupted checksum yields the payload
+            let corrupted = format!(
+                "{}{}",
+                encoded.get(..encoded.len() - word_length).unwrap(),
+                encode(&[0], &style).get(..word_length).unwrap()
+            );
+            assert_eq!(
+                decode_prefix(&corrupted, &style),
+                (input.clone(), Err(Error::InvalidChecksum))
+            );
+        }
+        assert_eq!(
+            decode_prefix("able acid xxxx lava", &Style::Standard),
+            (vec![0, 1], Err(Error::InvalidWord))
+        );
+        assert_eq!(
+            decode_prefix("", &Style::Uri),
+            (
+                vec![],
+                Err(Error::Truncated {
+                    missing_chars_at_least: 19
+                })
+            )
+        );
     }
 
     #[test]
@@ -0,0 +1,374 @@
+//! Split a message into single-part URIs which are reassembled in any order.
+//!
+//! Where animation isn't possible, e.g. when writing a message to several NFC
+//! tags, the parts of a multi-part transmission can't be displayed one after
+//! another. Instead, [`encode`] splits a message into a fixed number of chunks,
+//! each carried by an independent single-part URI, and a [`Decoder`] reassembles
+//! them once all have been received, in whatever order.
+//!
+//! The payload of each URI is the CBOR chunk envelope
+//! ```text
+//! #6.1970430824([sequence: uint, count: uint, digest: bytes .size 32, data: bytes])
+//! ```
+//! where the tag is [`ENVELOPE_TAG`], `sequence` is the one-based position of the
+//! chunk among the `count` chunks of the message, and `digest` is the SHA-256
+//! digest of the whole message. The tag keeps envelopes from being mistaken for
+//! plain payloads of the same type.
+//! ```
+//! let message = "Ten chars!".repeat(10);
+//! let mut chunks = ur::chunked::encode("bytes", message.as_bytes(), 40).unwrap();
+//! assert_eq!(chunks.len(), 3);
+//! chunks.reverse();
+//! let mut decoder = ur::chunked::Decoder::default();
+//! for chunk in &chunks {
+//!     decoder.receive(chunk).unwrap();
+//! }
+//! assert_eq!(decoder.message().unwrap(), Some(message.into_bytes()));
+//! ```
+
+use anyhow::Context;
+use serde_cbor::Value;
+
+/// The CBOR tag of a chunk envelope, the ASCII encoding of `urch`.
+pub const ENVELOPE_TAG: u64 = 0x7572_6368;
+
+/// Splits a message into chunks of the given size and returns the single-part
+/// URIs of the given type carrying them.
+///
+/// # Examples
+///
+/// See the [`crate::chunked`] module documentation for an example.
+///
+/// # Errors
+///
+/// If an empty message, a zero chunk size or an invalid type is passed,
+/// an error will be returned.
+pub fn encode(ur_type: &str, message: &[u8], chunk_size: usize) -> anyhow::Result<Vec<String>> {
+    anyhow::ensure!(!message.is_empty(), "expected non-empty message");
+    anyhow::ensure!(chunk_size > 0, "expected positive chunk size");
+    let digest = digest(message);
+    let count = (message.len() - 1) / chunk_size + 1;
+    message
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, data)| {
+            let envelope = Value::Tag(
+                ENVELOPE_TAG,
+                Box::new(Value::Array(vec![
+                    Value::from(index as u64 + 1),
+                    Value::from(count as u64),
+                    Value::Bytes(digest.to_vec()),
+                    Value::Bytes(data.to_vec()),
+                ])),
+            );
+            crate::ur::encode_ur(ur_type, &serde_cbor::to_vec(&envelope)?)
+        })
+        .collect()
+}
+
+/// A decoder reassembling the chunks produced by [`encode`].
+///
+/// # Examples
+///
+/// See the [`crate::chunked`] module documentation for an example.
+///
+/// The chunks are stored as they arrive, so a forged chunk count doesn't make
+/// the decoder allocate for chunks which haven't been received.
+#[derive(Default)]
+pub struct Decoder {
+    ur_type: Option<String>,
+    digest: [u8; 32],
+    count: usize,
+    chunks: std::collections::BTreeMap<usize, Vec<u8>>,
+}
+
+impl Decoder {
+    /// Receives a URI carrying a chunk into the decoder.
+    ///
+    /// Chunks which have been received before are ignored.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::chunked`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// If the URI is malformed, doesn't carry a chunk envelope, or the chunk
+    /// belongs to a different message than the previously received ones,
+    /// an error will be returned.
+    pub fn receive(&mut self, value: &str) -> anyhow::Result<()> {
+        let ur = crate::ur::UR::parse(value)?;
+        anyhow::ensure!(
+            ur.kind() == crate::ur::Kind::SinglePart,
+            "expected single-part ur"
+        );
+        let (sequence, count, digest, data) =
+            parse_envelope(&ur.decode_payload()?).context("invalid chunk envelope")?;
+        anyhow::ensure!(
+            count > 0 && (1..=count).contains(&sequence),
+            "invalid chunk {}-{}",
+            sequence,
+            count
+        );
+        if let Some(ur_type) = &self.ur_type {
+            anyhow::ensure!(
+                ur_type == ur.ur_type() && self.count == count && self.digest == digest,
+                "chunk belongs to a different message"
+            );
+        } else {
+            self.ur_type = Some(ur.ur_type().to_string());
+            self.digest = digest;
+            self.count = count;
+        }
+        self.chunks.entry(sequence).or_insert(data);
+        Ok(())
+    }
+
+    /// Returns whether all chunks have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chunks = ur::chunked::encode("bytes", b"data", 3).unwrap();
+    /// let mut decoder = ur::chunked::Decoder::default();
+    /// assert!(!decoder.complete());
+    /// decoder.receive(&chunks[1]).unwrap();
+    /// assert!(!decoder.complete());
+    /// decoder.receive(&chunks[0]).unwrap();
+    /// assert!(decoder.complete());
+    /// ```
+    #[must_use]
+    pub fn complete(&self) -> bool {
+        self.count > 0 && self.chunks.len() == self.count
+    }
+
+    /// Returns the type of the received chunks, `None` if none has been received yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chunks = ur::chunked::encode("crypto-psbt", b"data", 3).unwrap();
+    /// let mut decoder = ur::chunked::Decoder::default();
+    /// decoder.receive(&chunks[0]).unwrap();
+    /// assert_eq!(decoder.ur_type(), Some("crypto-psbt"));
+    /// ```
+    #[must_use]
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// If [`complete`], returns the reassembled message, `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::chunked`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// If the reassembled message doesn't match the digest of the chunks,
+    /// an error will be returned.
+    ///
+    /// [`complete`]: Decoder::complete
+    pub fn message(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        if !self.complete() {
+            return Ok(None);
+        }
+        let message: Vec<u8> = self.chunks.values().flatten().copied().collect();
+        anyhow::ensure!(digest(&message) == self.digest, "message digest mismatch");
+        Ok(Some(message))
+    }
+
+    /// Discards all received chunks to receive another message, e.g. after
+    /// the reassembled message failed to match its digest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut decoder = ur::chunked::Decoder::default();
+    /// for message in ["data", "other data"] {
+    ///     for chunk in ur::chunked::encode("bytes", message.as_bytes(), 3).unwrap() {
+    ///         decoder.receive(&chunk).unwrap();
+    ///     }
+    ///     assert_eq!(decoder.message().unwrap(), Some(message.as_bytes().to_vec()));
+    ///     decoder.clear();
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn digest(message: &[u8]) -> [u8; 32] {
+    use bitcoin_hashes::Hash;
+    bitcoin_hashes::sha256::Hash::hash(message).into_inner()
+}
+
+fn parse_envelope(cbor: &[u8]) -> anyhow::Result<(usize, usize, [u8; 32], Vec<u8>)> {
+    let items = match serde_cbor::from_slice(cbor)? {
+        Value::Tag(ENVELOPE_TAG, content) => match *content {
+            Value::Array(items) => items,
+            _ => anyhow::bail!("expected array"),
+        },
+        _ => anyhow::bail!("expected tag {}", ENVELOPE_TAG),
+    };
+    match <[Value; 4]>::try_from(items) {
+        Ok(
+            [Value::Integer(sequence), Value::Integer(count), Value::Bytes(digest), Value::Bytes(data)],
+        ) => Ok((
+            usize::try_from(sequence)?,
+            usize::try_from(count)?,
+            <[u8; 32]>::try_from(digest).map_err(|_| anyhow::anyhow!("expected 32-byte digest"))?,
+            data,
+        )),
+        _ => anyhow::bail!("expected sequence, count, digest and data"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let chunks = encode("bytes", b"data", 3).unwrap();
+        let ur = crate::ur::UR::parse(chunks.first().unwrap()).unwrap();
+        let payload = ur.decode_payload().unwrap();
+        assert_eq!(
+            hex::encode(payload),
+            [
+                "da75726368",
+                "84",
+                "01",
+                "02",
+                "5820",
+                "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7",
+                "43",
+                "646174",
+            ]
+            .concat()
+        );
+        assert_eq!(
+            encode("bytes", b"", 3).unwrap_err().to_string(),
+            "expected non-empty message"
+        );
+        assert_eq!(
+            encode("bytes", b"data", 0).unwrap_err().to_string(),
+            "expected positive chunk size"
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_and_duplicates() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 1000);
+        let chunks = encode("bytes", &message, 64).unwrap();
+        assert_eq!(chunks.len(), 16);
+        let mut decoder = Decoder::default();
+        for index in [3, 15, 3, 0, 7, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 15] {
+            assert!(!decoder.complete());
+            assert_eq!(decoder.message().unwrap(), None);
+            decoder.receive(chunks.get(index).unwrap()).unwrap();
+        }
+        decoder.receive(chunks.get(14).unwrap()).unwrap();
+        assert!(decoder.complete());
+        assert_eq!(decoder.message().unwrap(), Some(message));
+    }
+
+    #[test]
+    fn test_decoder_errors() {
+        let chunks = encode("bytes", b"data", 3).unwrap();
+        let other = encode("bytes", b"date", 3).unwrap();
+        let mut decoder = Decoder::default();
+        decoder.receive(chunks.first().unwrap()).unwrap();
+        assert_eq!(
+            decoder
+                .receive(other.get(1).unwrap())
+                .unwrap_err()
+                .to_string(),
+            "chunk belongs to a different message"
+        );
+        assert_eq!(
+            decoder
+                .receive(&chunks.get(1).unwrap().replace("bytes", "other"))
+                .unwrap_err()
+                .to_string(),
+            "chunk belongs to a different message"
+        );
+        // a plain payload is not a chunk
+        assert_eq!(
+            decoder
+                .receive(&crate::ur::encode_ur("bytes", b"data").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "invalid chunk envelope"
+        );
+        let mut encoder = crate::Encoder::new(b"data", 3, "bytes").unwrap();
+        assert_eq!(
+            decoder
+                .receive(&encoder.next_part().unwrap())
+                .unwrap_err()
+                .to_string(),
+            "expected single-part ur"
+        );
+
+        // chunks carrying data which doesn't match the digest
+        let forged = |sequence: u64, data: &[u8]| {
+            let envelope = Value::Tag(
+                ENVELOPE_TAG,
+                Box::new(Value::Array(vec![
+                    Value::from(sequence),
+                    Value::from(2_u64),
+                    Value::Bytes(digest(b"data").to_vec()),
+                    Value::Bytes(data.to_vec()),
+                ])),
+            );
+            crate::ur::encode_ur("bytes", &serde_cbor::to_vec(&envelope).unwrap()).unwrap()
+        };
+        let mut decoder = Decoder::default();
+        decoder.receive(&forged(1, b"dat")).unwrap();
+        decoder.receive(&forged(2, b"e")).unwrap();
+        assert!(decoder.complete());
+        assert_eq!(
+            decoder.message().unwrap_err().to_string(),
+            "message digest mismatch"
+        );
+        // the decoder recovers from the forged chunks once cleared
+        decoder.clear();
+        assert!(!decoder.complete());
+        assert_eq!(decoder.ur_type(), None);
+        for chunk in encode("bytes", b"data", 3).unwrap() {
+            decoder.receive(&chunk).unwrap();
+        }
+        assert_eq!(decoder.message().unwrap(), Some(b"data".to_vec()));
+        assert_eq!(
+            Decoder::default()
+                .receive(&forged(3, b"e"))
+                .unwrap_err()
+                .to_string(),
+            "invalid chunk 3-2"
+        );
+    }
+
+    #[test]
+    fn test_oversized_count() {
+        let forged = |sequence: u64, count: u64| {
+            let envelope = Value::Tag(
+                ENVELOPE_TAG,
+                Box::new(Value::Array(vec![
+                    Value::from(sequence),
+                    Value::from(count),
+                    Value::Bytes(digest(b"data").to_vec()),
+                    Value::Bytes(b"data".to_vec()),
+                ])),
+            );
+            crate::ur::encode_ur("bytes", &serde_cbor::to_vec(&envelope).unwrap()).unwrap()
+        };
+        // the claimed count doesn't allocate anything up front
+        for count in [u64::try_from(usize::MAX / 2).unwrap(), 1_000_000_000] {
+            let mut decoder = Decoder::default();
+            decoder.receive(&forged(count, count)).unwrap();
+            decoder.receive(&forged(1, count)).unwrap();
+            assert!(!decoder.complete());
+            assert_eq!(decoder.message().unwrap(), None);
+        }
+    }
+}
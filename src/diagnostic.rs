@@ -0,0 +1,473 @@
+//! Render CBOR payloads in [diagnostic notation](https://www.rfc-editor.org/rfc/rfc8949.html#name-diagnostic-notation).
+//!
+//! This is useful to inspect the payload of a uniform resource which can't be
+//! interpreted otherwise. Malformed input is rendered up to the first error,
+//! followed by a comment stating the offending byte offset.
+//! ```
+//! let cbor = hex::decode("a201d8255002").unwrap();
+//! assert_eq!(
+//!     ur::diagnostic(&cbor),
+//!     "{1: 37( / error at offset 5: unexpected end of input /"
+//! );
+//! ```
+
+/// Upper bounds applied when rendering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum nesting depth of arrays, maps and tags.
+    /// The contents of more deeply nested items are elided as `...`.
+    pub max_depth: usize,
+    /// The maximum number of characters of the output.
+    /// Longer output is cut off and terminated by `...`.
+    pub max_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: 16,
+            max_length: 4096,
+        }
+    }
+}
+
+// Elided items still need to be traversed, this bounds the recursion.
+const MAX_NESTING: usize = 256;
+
+/// Renders a CBOR payload in diagnostic notation, respecting the given [`Limits`].
+///
+/// Multiple concatenated items are rendered as a comma-separated sequence.
+///
+/// # Examples
+///
+/// ```
+/// use ur::diagnostic::{render, Limits};
+/// let cbor = hex::decode("9f0182020304ff").unwrap();
+/// assert_eq!(render(&cbor, &Limits::default()), "[_ 1, [2, 3], 4]");
+/// let limits = Limits {
+///     max_depth: 1,
+///     max_length: 100,
+/// };
+/// assert_eq!(render(&cbor, &limits), "[_ 1, [...], 4]");
+/// ```
+#[must_use]
+pub fn render(cbor: &[u8], limits: &Limits) -> String {
+    let mut renderer = Renderer {
+        cbor,
+        position: 0,
+        output: String::new(),
+        limits: *limits,
+        elided: 0,
+    };
+    let mut result = Ok(());
+    while result.is_ok() && renderer.position < cbor.len() {
+        if renderer.position > 0 {
+            renderer.write(", ");
+        }
+        result = renderer.item(0);
+    }
+    match result {
+        Ok(()) => {}
+        Err(Stop::Length) => {
+            let mut end = limits.max_length;
+            while !renderer.output.is_char_boundary(end) {
+                end -= 1;
+            }
+            renderer.output.truncate(end);
+            renderer.output.push_str("...");
+        }
+        Err(Stop::Malformed(message)) => {
+            renderer.output.truncate(renderer.output.trim_end().len());
+            if !renderer.output.is_empty() {
+                renderer.output.push(' ');
+            }
+            let position = renderer.position;
+            renderer.output.push_str(
+                &[
+                    "/ error at offset ",
+                    &position.to_string(),
+                    ": ",
+                    message,
+                    " /",
+                ]
+                .concat(),
+            );
+        }
+    }
+    renderer.output
+}
+
+enum Stop {
+    Length,
+    Malformed(&'static str),
+}
+
+struct Renderer<'a> {
+    cbor: &'a [u8],
+    position: usize,
+    output: String,
+    limits: Limits,
+    elided: usize,
+}
+
+impl<'a> Renderer<'a> {
+    fn write(&mut self, s: &str) {
+        if self.elided == 0 {
+            self.output.push_str(s);
+        }
+    }
+
+    fn check_length(&self) -> Result<(), Stop> {
+        if self.output.len() > self.limits.max_length {
+            return Err(Stop::Length);
+        }
+        Ok(())
+    }
+
+    fn peek(&self) -> Result<u8, Stop> {
+        self.cbor
+            .get(self.position)
+            .copied()
+            .ok_or(Stop::Malformed("unexpected end of input"))
+    }
+
+    fn bytes(&mut self, length: u64) -> Result<&'a [u8], Stop> {
+        let end = usize::try_from(length)
+            .ok()
+            .and_then(|length| self.position.checked_add(length))
+            .ok_or(Stop::Malformed("unexpected end of input"))?;
+        let bytes = self
+            .cbor
+            .get(self.position..end)
+            .ok_or(Stop::Malformed("unexpected end of input"))?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn argument(&mut self, info: u8) -> Result<Option<u64>, Stop> {
+        let length = match info {
+            0..=23 => return Ok(Some(info.into())),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            31 => return Ok(None),
+            _ => return Err(Stop::Malformed("invalid additional information")),
+        };
+        Ok(Some(
+            self.bytes(length)?
+                .iter()
+                .fold(0, |acc, &b| (acc << 8) | u64::from(b)),
+        ))
+    }
+
+    fn item(&mut self, depth: usize) -> Result<(), Stop> {
+        let initial = self.peek()?;
+        self.position += 1;
+        self.item_with_initial_byte(initial, depth)?;
+        self.check_length()
+    }
+
+    fn item_with_initial_byte(&mut self, initial: u8, depth: usize) -> Result<(), Stop> {
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let argument = self.argument(info)?;
+        match (major, argument) {
+            (0, Some(value)) => self.write(&value.to_string()),
+            (1, Some(value)) => self.write(&(-1 - i128::from(value)).to_string()),
+            (2, Some(length)) => {
+                let bytes = self.bytes(length)?;
+                self.write(&format!("h'{}'", hex::encode(bytes)));
+            }
+            (3, Some(length)) => {
+                let bytes = self.bytes(length)?;
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|_| Stop::Malformed("invalid UTF-8 in text string"))?;
+                self.write(&format!("{text:?}"));
+            }
+            (2 | 3, None) => self.chunks(major, depth)?,
+            (4, length) => self.container(length, false, depth)?,
+            (5, length) => self.container(length, true, depth)?,
+            (6, Some(tag)) => {
+                self.write(&format!("{tag}("));
+                self.nested(depth, Renderer::item)?;
+                self.write(")");
+            }
+            (7, Some(value)) => self.simple(info, value),
+            (7, None) => return Err(Stop::Malformed("unexpected break")),
+            _ => return Err(Stop::Malformed("invalid indefinite length")),
+        }
+        Ok(())
+    }
+
+    fn chunks(&mut self, major: u8, depth: usize) -> Result<(), Stop> {
+        self.write("(_ ");
+        let mut first = true;
+        loop {
+            let initial = self.peek()?;
+            self.position += 1;
+            if initial == 0xff {
+                break;
+            }
+            if initial >> 5 != major || initial & 0x1f == 31 {
+                return Err(Stop::Malformed("invalid chunk in indefinite-length string"));
+            }
+            if !first {
+                self.write(", ");
+            }
+            first = false;
+            self.item_with_initial_byte(initial, depth)?;
+            self.check_length()?;
+        }
+        self.write(")");
+        Ok(())
+    }
+
+    fn container(&mut self, length: Option<u64>, map: bool, depth: usize) -> Result<(), Stop> {
+        let (open, close) = if map { ("{", "}") } else { ("[", "]") };
+        self.write(open);
+        if length.is_none() {
+            self.write("_ ");
+        }
+        self.nested(depth, |renderer, depth| {
+            let mut count = 0;
+            loop {
+                match length {
+                    Some(length) if count == length => break,
+                    None if renderer.peek()? == 0xff => {
+                        renderer.position += 1;
+                        break;
+                    }
+                    _ => {}
+                }
+                if count > 0 {
+                    renderer.write(", ");
+                }
+                renderer.item(depth)?;
+                if map {
+                    renderer.write(": ");
+                    renderer.item(depth)?;
+                }
+                count += 1;
+            }
+            Ok(())
+        })?;
+        self.write(close);
+        Ok(())
+    }
+
+    fn nested<F>(&mut self, depth: usize, f: F) -> Result<(), Stop>
+    where
+        F: FnOnce(&mut Self, usize) -> Result<(), Stop>,
+    {
+        if depth >= MAX_NESTING {
+            return Err(Stop::Malformed("maximum nesting depth exceeded"));
+        }
+        let elide = depth >= self.limits.max_depth;
+        if elide {
+            self.write("...");
+            self.elided += 1;
+        }
+        let result = f(self, depth + 1);
+        if elide {
+            self.elided -= 1;
+        }
+        result
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn simple(&mut self, info: u8, value: u64) {
+        let rendered = match info {
+            20 => "false".to_string(),
+            21 => "true".to_string(),
+            22 => "null".to_string(),
+            23 => "undefined".to_string(),
+            25 => float(half_to_f64(value as u16)),
+            26 => float(f64::from(f32::from_bits(value as u32))),
+            27 => float(f64::from_bits(value)),
+            _ => format!("simple({value})"),
+        };
+        self.write(&rendered);
+    }
+}
+
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+fn half_to_f64(bits: u16) -> f64 {
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => mantissa * 2_f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2_f64.powi(exponent - 25),
+    };
+    if bits & 0x8000 == 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diag(cbor: &str) -> String {
+        crate::diagnostic(&hex::decode(cbor).unwrap())
+    }
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(diag("00"), "0");
+        assert_eq!(diag("17"), "23");
+        assert_eq!(diag("1818"), "24");
+        assert_eq!(diag("1903e8"), "1000");
+        assert_eq!(diag("1bffffffffffffffff"), "18446744073709551615");
+        assert_eq!(diag("20"), "-1");
+        assert_eq!(diag("3bffffffffffffffff"), "-18446744073709551616");
+        assert_eq!(diag("40"), "h''");
+        assert_eq!(diag("4401020304"), "h'01020304'");
+        assert_eq!(diag("60"), "\"\"");
+        assert_eq!(diag("6449455446"), "\"IETF\"");
+        assert_eq!(diag("62225c"), "\"\\\"\\\\\"");
+        assert_eq!(diag("f4"), "false");
+        assert_eq!(diag("f5"), "true");
+        assert_eq!(diag("f6"), "null");
+        assert_eq!(diag("f7"), "undefined");
+        assert_eq!(diag("f0"), "simple(16)");
+        assert_eq!(diag("f8ff"), "simple(255)");
+    }
+
+    #[test]
+    fn test_floats() {
+        assert_eq!(diag("f90000"), "0.0");
+        assert_eq!(diag("f98000"), "-0.0");
+        assert_eq!(diag("f93c00"), "1.0");
+        assert_eq!(diag("f93e00"), "1.5");
+        assert_eq!(diag("f97bff"), "65504.0");
+        assert_eq!(diag("f90001"), "5.960464477539063e-8");
+        assert_eq!(diag("f97c00"), "Infinity");
+        assert_eq!(diag("f9fc00"), "-Infinity");
+        assert_eq!(diag("f97e00"), "NaN");
+        assert_eq!(diag("fa47c35000"), "100000.0");
+        assert_eq!(diag("fb3ff199999999999a"), "1.1");
+        assert_eq!(diag("fbc010666666666666"), "-4.1");
+    }
+
+    #[test]
+    fn test_containers() {
+        assert_eq!(diag("80"), "[]");
+        assert_eq!(diag("83010203"), "[1, 2, 3]");
+        assert_eq!(diag("a0"), "{}");
+        assert_eq!(diag("a201020304"), "{1: 2, 3: 4}");
+        assert_eq!(diag("a26161016162820203"), "{\"a\": 1, \"b\": [2, 3]}");
+        assert_eq!(
+            diag("c074323031332d30332d32315432303a30343a30305a"),
+            "0(\"2013-03-21T20:04:00Z\")"
+        );
+        assert_eq!(diag("9fff"), "[_ ]");
+        assert_eq!(diag("9f018202039f0405ffff"), "[_ 1, [2, 3], [_ 4, 5]]");
+        assert_eq!(
+            diag("bf61610161629f0203ffff"),
+            "{_ \"a\": 1, \"b\": [_ 2, 3]}"
+        );
+        assert_eq!(diag("5f42010243030405ff"), "(_ h'0102', h'030405')");
+        assert_eq!(
+            diag("7f657374726561646d696e67ff"),
+            "(_ \"strea\", \"ming\")"
+        );
+        // a sequence of items
+        assert_eq!(diag("0102"), "1, 2");
+    }
+
+    #[test]
+    fn test_fountain_part() {
+        assert_eq!(
+            diag("8501091901001a0167aa07581d916ec65cf77cadf55cd7f9cda1a1030026ddd42e905b77adc36e4f2d3c"),
+            "[1, 9, 256, 23570951, h'916ec65cf77cadf55cd7f9cda1a1030026ddd42e905b77adc36e4f2d3c']"
+        );
+    }
+
+    #[test]
+    fn test_crypto_request() {
+        // https://github.com/BlockchainCommons/crypto-commons/blob/67ea252f4a7f295bb347cb046796d5b445b3ad3c/Docs/ur-99-request-response.md#the-seed-request
+        let data = crate::bytewords::decode(
+            "oeadtpdagdaobncpftlnylfgfgmuztihbawfsgrtflaotaadwkoyadtaaohdhdcxvsdkfgkepezepefrrffmbnnbmdvahnptrdtpbtuyimmemweootjshsmhlunyeslnameyhsdi",
+            &crate::bytewords::Style::Minimal,
+        )
+        .unwrap();
+        assert_eq!(
+            crate::diagnostic(&data),
+            "{1: 37(h'020c223a86f7464693fc650ef3cac047'), 2: 500({1: 600(h'e824467caffeaf3bbc3e0ca095e660a9bad80ddb6a919433a37161908b9a3986')})}"
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(diag("18"), "/ error at offset 1: unexpected end of input /");
+        assert_eq!(
+            diag("8301"),
+            "[1, / error at offset 2: unexpected end of input /"
+        );
+        assert_eq!(
+            diag("1c"),
+            "/ error at offset 1: invalid additional information /"
+        );
+        assert_eq!(diag("ff"), "/ error at offset 1: unexpected break /");
+        assert_eq!(
+            diag("1f"),
+            "/ error at offset 1: invalid indefinite length /"
+        );
+        assert_eq!(
+            diag("62c328"),
+            "/ error at offset 3: invalid UTF-8 in text string /"
+        );
+        assert_eq!(
+            diag("5f41016102ff"),
+            "(_ h'01' / error at offset 4: invalid chunk in indefinite-length string /"
+        );
+        assert_eq!(
+            diag("5b0000000100000000"),
+            "/ error at offset 9: unexpected end of input /"
+        );
+        assert!(diag(&"81".repeat(1000)).ends_with("maximum nesting depth exceeded /"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let cbor = hex::decode("82818181018181818102").unwrap();
+        assert_eq!(render(&cbor, &Limits::default()), "[[[[1]]], [[[[2]]]]]");
+        let limits = Limits {
+            max_depth: 2,
+            max_length: 100,
+        };
+        assert_eq!(render(&cbor, &limits), "[[[...]], [[...]]]");
+        let limits = Limits {
+            max_depth: 0,
+            max_length: 100,
+        };
+        assert_eq!(render(&cbor, &limits), "[...]");
+        let limits = Limits {
+            max_depth: 16,
+            max_length: 5,
+        };
+        assert_eq!(render(&cbor, &limits), "[[[[1...");
+        let limits = Limits {
+            max_depth: 16,
+            max_length: 3,
+        };
+        assert_eq!(
+            render(&hex::decode("63c3a4c3").unwrap(), &limits),
+            "/ error at offset 4: invalid UTF-8 in text string /"
+        );
+        assert_eq!(render(&hex::decode("62c3a4").unwrap(), &limits), "\"ä...");
+    }
+}
@@ -0,0 +1,288 @@
+//! Estimate the duration of a transfer before starting it.
+//!
+//! A multi-part transfer displays one part per frame, e.g. as an animated QR code,
+//! and the receiving side captures only some of them. [`transfer_estimate`]
+//! predicts how many parts the receiver needs to capture, how many frames are
+//! displayed meanwhile and how long this takes, based on a model of the fountain
+//! code overhead fitted to simulated transfers.
+//! ```
+//! let estimate = ur::estimate::transfer_estimate(1000, 100, 8.0, 0.75).unwrap();
+//! assert_eq!(estimate.fragment_count, 10);
+//! assert_eq!(estimate.parts, 14);
+//! assert_eq!(estimate.frames, 19);
+//! assert_eq!(estimate.duration, std::time::Duration::from_millis(2375));
+//! ```
+
+/// The excess of captured parts over the fragment count, relative to the latter,
+/// which a small loss of parts already incurs: a missing fragment can only be
+/// recovered from the mixed parts which happen to contain it.
+const SATURATED_OVERHEAD: f64 = 0.06;
+/// How fast the overhead approaches [`SATURATED_OVERHEAD`] with increasing loss.
+const SATURATION_RATE: f64 = 34.0;
+/// The further overhead per lost part.
+const LOSS_OVERHEAD: f64 = 0.98;
+
+/// The alphanumeric capacities of QR code versions 1 to 40 at error correction level L.
+const QR_CAPACITIES: [usize; 40] = [
+    25, 47, 77, 114, 154, 195, 224, 279, 335, 395, 468, 535, 619, 667, 758, 854, 938, 1046, 1153,
+    1249, 1352, 1460, 1588, 1704, 1853, 1990, 2132, 2223, 2369, 2520, 2677, 2840, 3009, 3183, 3351,
+    3537, 3729, 3927, 4087, 4296,
+];
+
+/// The predicted course of a transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    /// The number of fragments the message is split up into.
+    pub fragment_count: usize,
+    /// The number of parts the receiver needs to capture.
+    pub parts: usize,
+    /// The number of frames displayed until the receiver completes.
+    pub frames: usize,
+    /// The time it takes to display the frames.
+    pub duration: std::time::Duration,
+}
+
+/// Estimates a transfer of a message with the given maximum fragment length,
+/// displaying `fps` frames per second of which the receiver captures the
+/// fraction `capture_efficiency`.
+///
+/// The estimate is the expected course of a transfer, individual transfers
+/// may take longer or shorter. Returns `None` for an empty message, a zero
+/// maximum fragment length, a non-positive frame rate, or a capture efficiency
+/// outside of `(0, 1]`.
+///
+/// # Examples
+///
+/// See the [`crate::estimate`] module documentation for an example.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_precision_loss)]
+#[allow(clippy::cast_sign_loss)]
+pub fn transfer_estimate(
+    message_length: usize,
+    max_fragment_length: usize,
+    fps: f32,
+    capture_efficiency: f32,
+) -> Option<Estimate> {
+    if message_length == 0
+        || max_fragment_length == 0
+        || !(fps > 0.0 && fps.is_finite())
+        || !(capture_efficiency > 0.0 && capture_efficiency <= 1.0)
+    {
+        return None;
+    }
+    let fragment_count = crate::fountain::sequence_count(message_length, max_fragment_length);
+    let parts = if fragment_count == 1 {
+        1
+    } else {
+        let loss = 1.0 - f64::from(capture_efficiency);
+        let overhead =
+            SATURATED_OVERHEAD * (1.0 - (-SATURATION_RATE * loss).exp()) + LOSS_OVERHEAD * loss;
+        (fragment_count as f64 * (1.0 + overhead)).ceil() as usize
+    };
+    let frames = (parts as f64 / f64::from(capture_efficiency)).ceil() as usize;
+    Some(Estimate {
+        fragment_count,
+        parts,
+        frames,
+        duration: std::time::Duration::from_secs_f64(frames as f64 / f64::from(fps)),
+    })
+}
+
+/// Estimates a transfer of a message of the given type as QR codes of the
+/// given version, see [`transfer_estimate`].
+///
+/// The parts are sized to fit the alphanumeric capacity of the version at
+/// error correction level L, as with [`crate::transfer::send`]. Returns `None`
+/// if the version is not between 1 and 40 or too small to fit any fragment.
+///
+/// # Examples
+///
+/// ```
+/// let estimate =
+///     ur::estimate::transfer_estimate_for_qr_version(1000, "bytes", 10, 8.0, 0.75).unwrap();
+/// assert_eq!(estimate.fragment_count, 7);
+/// assert_eq!(ur::estimate::transfer_estimate_for_qr_version(1000, "bytes", 2, 8.0, 0.75), None);
+/// ```
+#[must_use]
+pub fn transfer_estimate_for_qr_version(
+    message_length: usize,
+    ur_type: &str,
+    version: usize,
+    fps: f32,
+    capture_efficiency: f32,
+) -> Option<Estimate> {
+    let capacity = version
+        .checked_sub(1)
+        .and_then(|index| QR_CAPACITIES.get(index))?;
+    let max_fragment_length = crate::transfer::max_fragment_length(ur_type, *capacity)?;
+    transfer_estimate(message_length, max_fragment_length, fps, capture_efficiency)
+}
+
+/// Returns the smallest QR code version whose alphanumeric capacity at error
+/// correction level L fits a part of the given length, or `None` if the part
+/// exceeds the capacity of version 40.
+///
+/// # Examples
+///
+/// ```
+/// let part_length = ur::transfer::max_part_length("bytes", 100);
+/// assert_eq!(ur::estimate::qr_version(part_length), Some(9));
+/// assert_eq!(ur::estimate::qr_version(5000), None);
+/// ```
+#[must_use]
+pub fn qr_version(part_length: usize) -> Option<usize> {
+    QR_CAPACITIES
+        .iter()
+        .position(|&capacity| capacity >= part_length)
+        .map(|index| index + 1)
+}
+
+/// Simulates transfers of a message over a channel capturing the fraction
+/// `capture_efficiency` of the frames, and returns the average number of
+/// frames displayed until the receiver completes.
+///
+/// The simulation is seeded with the message and hence deterministic.
+///
+/// # Examples
+///
+/// ```
+/// let frames = ur::estimate::simulate_frames(&[0; 1000], 100, 0.5, 10).unwrap();
+/// let estimate = ur::estimate::transfer_estimate(1000, 100, 8.0, 0.5).unwrap();
+/// assert!((frames - estimate.frames as f64).abs() < 0.2 * frames);
+/// ```
+///
+/// # Errors
+///
+/// If an empty message, a zero maximum fragment length or number of runs,
+/// or a capture efficiency outside of `(0, 1]` is passed, an error will be returned.
+#[allow(clippy::cast_precision_loss)]
+pub fn simulate_frames(
+    message: &[u8],
+    max_fragment_length: usize,
+    capture_efficiency: f32,
+    runs: usize,
+) -> anyhow::Result<f64> {
+    anyhow::ensure!(
+        capture_efficiency > 0.0 && capture_efficiency <= 1.0,
+        "expected capture efficiency in (0, 1]"
+    );
+    anyhow::ensure!(runs > 0, "expected positive number of runs");
+    let mut rng = crate::xoshiro::Xoshiro256::from(message);
+    let mut frames = 0_usize;
+    for _ in 0..runs {
+        let mut encoder = crate::fountain::Encoder::new(message, max_fragment_length)?;
+        let mut decoder = crate::fountain::Decoder::default();
+        while !decoder.complete() {
+            let part = encoder.next_part();
+            frames += 1;
+            if rng.next_double() < f64::from(capture_efficiency) {
+                decoder.receive(part)?;
+            }
+        }
+    }
+    Ok(frames as f64 / runs as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_capacities() {
+        for (index, &capacity) in QR_CAPACITIES.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            #[allow(clippy::cast_possible_wrap)]
+            let version = qrcode::Version::Normal(index as i16 + 1);
+            let fits = |length: usize| {
+                qrcode::QrCode::with_version("A".repeat(length), version, qrcode::EcLevel::L)
+                    .is_ok()
+            };
+            assert!(fits(capacity));
+            assert!(!fits(capacity + 1));
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert_eq!(transfer_estimate(0, 10, 8.0, 0.5), None);
+        assert_eq!(transfer_estimate(100, 0, 8.0, 0.5), None);
+        assert_eq!(transfer_estimate(100, 10, 0.0, 0.5), None);
+        assert_eq!(transfer_estimate(100, 10, f32::INFINITY, 0.5), None);
+        assert_eq!(transfer_estimate(100, 10, 8.0, 0.0), None);
+        assert_eq!(transfer_estimate(100, 10, 8.0, 1.5), None);
+        assert_eq!(
+            transfer_estimate_for_qr_version(100, "bytes", 0, 8.0, 0.5),
+            None
+        );
+        assert_eq!(
+            transfer_estimate_for_qr_version(100, "bytes", 41, 8.0, 0.5),
+            None
+        );
+        assert_eq!(
+            transfer_estimate(100, 200, 8.0, 0.5),
+            Some(Estimate {
+                fragment_count: 1,
+                parts: 1,
+                frames: 2,
+                duration: std::time::Duration::from_millis(250),
+            })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_overhead_model() {
+        // the fragment count equals the message length for fragments of a single byte
+        for fragment_count in [20, 100] {
+            let message = crate::xoshiro::test_utils::make_message("estimate", fragment_count);
+            for capture_efficiency in [1.0, 0.9, 0.75, 0.5] {
+                let simulated = simulate_frames(&message, 1, capture_efficiency, 20).unwrap();
+                let estimate = transfer_estimate(fragment_count, 1, 8.0, capture_efficiency)
+                    .unwrap()
+                    .frames as f64;
+                assert!(
+                    (estimate - simulated).abs() < 0.15 * simulated,
+                    "{fragment_count} fragments at {capture_efficiency}: \
+                     estimated {estimate}, simulated {simulated}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_planning_helpers() {
+        assert_eq!(qr_version(0), Some(1));
+        assert_eq!(qr_version(25), Some(1));
+        assert_eq!(qr_version(26), Some(2));
+        assert_eq!(qr_version(4296), Some(40));
+        assert_eq!(qr_version(4297), None);
+        for max_fragment_length in [1, 10, 100, 1000] {
+            let part_length = crate::transfer::max_part_length("bytes", max_fragment_length);
+            let mut encoder =
+                crate::Encoder::new(&[0; 5000], max_fragment_length, "bytes").unwrap();
+            assert!(encoder.next_part().unwrap().len() <= part_length);
+        }
+        assert_eq!(
+            simulate_frames(&[0; 10], 1, 1.0, 3).unwrap(),
+            10.0,
+            "a lossless channel completes after a single pass"
+        );
+        assert_eq!(
+            simulate_frames(&[0; 10], 1, 0.0, 3)
+                .unwrap_err()
+                .to_string(),
+            "expected capture efficiency in (0, 1]"
+        );
+        assert_eq!(
+            simulate_frames(&[0; 10], 1, 1.0, 0)
+                .unwrap_err()
+                .to_string(),
+            "expected positive number of runs"
+        );
+        assert_eq!(
+            simulate_frames(&[], 1, 1.0, 1).unwrap_err().to_string(),
+            "expected non-empty message"
+        );
+    }
+}
@@ -6,7 +6,9 @@
 //! payload segments, or constructed by xor-ing a certain set of payload segments.
 //!
 //! A seeded `Xoshiro` RNG ensures that the receiver can reconstruct which segments
-//! were combined into the part.
+//! were combined into the part. The reconstruction draws the number of segments
+//! from the degree distribution of the specification, so the distribution is
+//! fixed: a decoder can't reconstruct the segments of parts mixed with another one.
 //! ```
 //! let xor = |a: &[u8], b: &[u8]| {
 //!     a.iter()
@@ -87,15 +89,19 @@ use serde_cbor::Value;
 
 /// An encoder capable of emitting fountain-encoded transmissions.
 ///
+/// A clone continues the transmission from the current sequence number
+/// independently of the original. Cloning copies the fragments of the message.
+///
 /// # Examples
 ///
 /// See the [`crate::fountain`] module documentation for an example.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Encoder {
     parts: Vec<Vec<u8>>,
     message_length: usize,
     checksum: u32,
     current_sequence: usize,
+    chooser: FragmentChooser,
 }
 
 impl Encoder {
@@ -119,21 +125,151 @@ impl Encoder {
     ///
     /// # Errors
     ///
-    /// If an empty message or a zero maximum fragment length is passed, an error
-    /// will be returned.
+    /// If an empty message is passed, an [`EmptyMessage`] error will be returned.
+    /// If a zero maximum fragment length is passed, a [`ZeroFragmentLength`]
+    /// error will be returned.
     pub fn new(message: &[u8], max_fragment_length: usize) -> anyhow::Result<Self> {
         if message.is_empty() {
-            anyhow::bail!("expected non-empty message")
+            return Err(EmptyMessage.into());
         }
         if max_fragment_length == 0 {
-            anyhow::bail!("expected positive maximum fragment length")
+            return Err(ZeroFragmentLength.into());
         }
         let fragment_length = fragment_length(message.len(), max_fragment_length);
         let fragments = partition(message.to_vec(), fragment_length);
+        let checksum = crate::crc32().checksum(message);
         Ok(Self {
+            chooser: FragmentChooser::new(fragments.len(), checksum),
             parts: fragments,
             message_length: message.len(),
-            checksum: crate::crc32().checksum(message),
+            checksum,
+            current_sequence: 0,
+        })
+    }
+
+    /// Constructs a new [`Encoder`] using the given fragments as message segments,
+    /// instead of splitting up the message itself.
+    ///
+    /// The message is the concatenation of the fragments. All fragments must have
+    /// the same length, except for the last one which may be shorter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Decoder, Encoder};
+    /// let fragments: [&[u8]; 3] = [b"Ten ", b"char", b"s!"];
+    /// let mut encoder = Encoder::from_fragments(&fragments).unwrap();
+    /// assert_eq!(encoder.fragment_count(), 3);
+    /// let mut decoder = Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(encoder.next_part()).unwrap();
+    /// }
+    /// assert_eq!(decoder.message().unwrap().as_deref(), Some("Ten chars!".as_bytes()));
+    /// assert_eq!(decoder.fragment(2), Some("s!".as_bytes()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If no fragments or an empty first fragment are passed, an [`EmptyMessage`]
+    /// error will be returned. If fragments with inconsistent lengths are passed,
+    /// an error will be returned.
+    pub fn from_fragments(fragments: &[&[u8]]) -> anyhow::Result<Self> {
+        let fragment_length = fragments
+            .first()
+            .map(|fragment| fragment.len())
+            .filter(|&length| length > 0)
+            .ok_or(EmptyMessage)?;
+        let last = fragments.len() - 1;
+        for (index, fragment) in fragments.iter().enumerate() {
+            if fragment.len() > fragment_length
+                || (index < last && fragment.len() < fragment_length)
+                || fragment.is_empty()
+            {
+                anyhow::bail!(
+                    "fragment {} has length {}, expected {}",
+                    index,
+                    fragment.len(),
+                    fragment_length
+                )
+            }
+        }
+        let message = fragments.concat();
+        let checksum = crate::crc32().checksum(&message);
+        Ok(Self {
+            chooser: FragmentChooser::new(fragments.len(), checksum),
+            parts: partition(message.clone(), fragment_length),
+            message_length: message.len(),
+            checksum,
+            current_sequence: 0,
+        })
+    }
+
+    /// Constructs a new [`Encoder`] for a message of the given length read from
+    /// a reader, see [`Encoder::new`].
+    ///
+    /// The message is read in a single pass, directly into its fragments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::Encoder;
+    /// let mut encoder = Encoder::from_reader("Ten chars!".as_bytes(), 10, 4).unwrap();
+    /// let mut expected = Encoder::new("Ten chars!".as_bytes(), 4).unwrap();
+    /// assert_eq!(
+    ///     encoder.next_part().cbor().unwrap(),
+    ///     expected.next_part().cbor().unwrap()
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If a zero message length is passed, an [`EmptyMessage`] error will be
+    /// returned. If a zero maximum fragment length is passed, a
+    /// [`ZeroFragmentLength`] error will be returned. If reading fails or ends
+    /// before the message length, an error will be returned.
+    pub fn from_reader(
+        mut reader: impl std::io::Read,
+        message_length: usize,
+        max_fragment_length: usize,
+    ) -> anyhow::Result<Self> {
+        use std::io::Read;
+
+        if message_length == 0 {
+            return Err(EmptyMessage.into());
+        }
+        if max_fragment_length == 0 {
+            return Err(ZeroFragmentLength.into());
+        }
+        let fragment_length = fragment_length(message_length, max_fragment_length);
+        let crc = crate::crc32();
+        let mut digest = crc.digest();
+        let mut parts = Vec::with_capacity(sequence_count(message_length, max_fragment_length));
+        let mut remaining = message_length;
+        while remaining > 0 {
+            let length = remaining.min(fragment_length);
+            let mut fragment = Vec::with_capacity(fragment_length);
+            reader
+                .by_ref()
+                .take(length as u64)
+                .read_to_end(&mut fragment)?;
+            if fragment.len() < length {
+                anyhow::bail!(
+                    "message ended after {} of {} bytes",
+                    message_length - remaining + fragment.len(),
+                    message_length
+                )
+            }
+            digest.update(&fragment);
+            fragment.resize(fragment_length, 0);
+            parts.push(fragment);
+            remaining -= length;
+        }
+        let checksum = digest.finalize();
+        Ok(Self {
+            chooser: FragmentChooser::new(parts.len(), checksum),
+            parts,
+            message_length,
+            checksum,
             current_sequence: 0,
         })
     }
@@ -164,8 +300,8 @@ impl Encoder {
     /// See the [`crate::fountain`] module documentation for an example.
     pub fn next_part(&mut self) -> Part {
         self.current_sequence += 1;
-        let indexes = choose_fragments(self.current_sequence, self.parts.len(), self.checksum);
-        let init = vec![0; self.parts.get(0).unwrap().len()];
+        let indexes = self.chooser.choose(self.current_sequence);
+        let init = vec![0; self.parts.first().unwrap().len()];
         let mixed = indexes.into_iter().fold(init, |acc, item| {
             xor(acc.as_slice(), self.parts.get(item).unwrap())
         });
@@ -178,6 +314,13 @@ impl Encoder {
         }
     }
 
+    // Returns the message, i.e. the concatenated fragments without padding.
+    pub(crate) fn message(&self) -> Vec<u8> {
+        let mut message = self.parts.concat();
+        message.truncate(self.message_length);
+        message
+    }
+
     /// Returns the number of segments the original message has been split up into.
     ///
     /// # Examples
@@ -217,12 +360,332 @@ impl Encoder {
     }
 }
 
+/// Determines how a [`Decoder`] treats parts whose sequence number doesn't increase.
+///
+/// Legitimate senders may loop over their parts or restart their emission,
+/// hence the default is permissive. In security-sensitive contexts, where
+/// an attacker could inject parts into the transmission channel, a decreasing
+/// sequence number or a repeated one with different data is a sign of such
+/// an injection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SequencePolicy {
+    /// Parts are accepted in any order.
+    #[default]
+    Permissive,
+    /// Parts with a lower sequence number than the highest one received so far
+    /// are rejected with a [`SequenceRegression`], as are parts repeating that
+    /// sequence number with different data with a [`ConflictingPart`].
+    Strict,
+}
+
+/// The error returned by an [`Encoder`] for an empty message, and by a [`Decoder`]
+/// for a part claiming an empty message, which can't be split into fragments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmptyMessage;
+
+impl std::fmt::Display for EmptyMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected non-empty message")
+    }
+}
+
+impl std::error::Error for EmptyMessage {}
+
+impl EmptyMessage {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ur::fountain::EmptyMessage.code(), ur::ErrorCode(201));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(201)
+    }
+}
+
+/// The error returned by a complete [`Decoder`] when the decoded message doesn't
+/// match the checksum shared by its parts.
+///
+/// Unlike the `bytewords` checksum of a single part, which fails on a misread
+/// part, this indicates that the sender and the decoder disagree on the message,
+/// and the transfer has to be restarted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageChecksum {
+    /// The checksum shared by the parts.
+    pub expected: u32,
+    /// The checksum of the decoded message.
+    pub got: u32,
+}
+
+impl std::fmt::Display for MessageChecksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "message checksum mismatch: expected {:08x}, got {:08x}",
+            self.expected, self.got
+        )
+    }
+}
+
+impl std::error::Error for MessageChecksum {}
+
+impl MessageChecksum {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::MessageChecksum;
+    /// let error = MessageChecksum { expected: 1, got: 2 };
+    /// assert_eq!(error.code(), ur::ErrorCode(202));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(202)
+    }
+}
+
+/// The error returned by a [`Decoder`] for a part combining more fragments than
+/// its maximum accepted degree, see [`Decoder::with_max_accepted_degree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DegreeError {
+    /// The number of fragments combined into the part.
+    pub degree: usize,
+    /// The maximum accepted degree of the decoder.
+    pub max_accepted_degree: usize,
+}
+
+impl std::fmt::Display for DegreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "part degree {} exceeds maximum accepted degree {}",
+            self.degree, self.max_accepted_degree
+        )
+    }
+}
+
+impl std::error::Error for DegreeError {}
+
+impl DegreeError {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::DegreeError;
+    /// let error = DegreeError { degree: 3, max_accepted_degree: 2 };
+    /// assert_eq!(error.code(), ur::ErrorCode(203));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(203)
+    }
+}
+
+/// The error returned by a [`Decoder`] when a received part solves a fragment
+/// which has already been solved, with different data.
+///
+/// The checksum only covers the whole message, so a corrupted part may go
+/// unnoticed until the message is complete. A conflict pinpoints such a
+/// corruption earlier. The decoder keeps the first solution of the fragment.
+///
+/// Two mixed parts combining the same fragments with different data conflict
+/// as well, in which case the smallest index of these fragments is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FragmentConflict {
+    /// The index of the fragment.
+    pub index: usize,
+}
+
+impl std::fmt::Display for FragmentConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "fragment {} solved twice with different data",
+            self.index
+        )
+    }
+}
+
+impl std::error::Error for FragmentConflict {}
+
+impl FragmentConflict {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::FragmentConflict;
+    /// assert_eq!(FragmentConflict { index: 0 }.code(), ur::ErrorCode(204));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(204)
+    }
+}
+
+/// The error returned by a [`Decoder`] with [`SequencePolicy::Strict`] for a
+/// part whose sequence number is lower than the highest one received so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SequenceRegression {
+    /// The highest sequence number received so far.
+    pub last: usize,
+    /// The sequence number of the part.
+    pub got: usize,
+}
+
+impl std::fmt::Display for SequenceRegression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "sequence regression: last {}, got {}",
+            self.last, self.got
+        )
+    }
+}
+
+impl std::error::Error for SequenceRegression {}
+
+impl SequenceRegression {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::SequenceRegression;
+    /// let error = SequenceRegression { last: 2, got: 1 };
+    /// assert_eq!(error.code(), ur::ErrorCode(205));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(205)
+    }
+}
+
+/// The error returned by a [`Decoder`] with [`SequencePolicy::Strict`] for a
+/// part repeating the highest sequence number received so far with different data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConflictingPart {
+    /// The sequence number of the part.
+    pub sequence: usize,
+}
+
+impl std::fmt::Display for ConflictingPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conflicting part for sequence {}", self.sequence)
+    }
+}
+
+impl std::error::Error for ConflictingPart {}
+
+impl ConflictingPart {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::ConflictingPart;
+    /// assert_eq!(ConflictingPart { sequence: 1 }.code(), ur::ErrorCode(206));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(206)
+    }
+}
+
+/// The error returned by a [`Decoder`] for a part which doesn't belong to the
+/// message of the previously received parts, see [`Decoder::validate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InconsistentPart;
+
+impl std::fmt::Display for InconsistentPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "part is inconsistent with previous ones")
+    }
+}
+
+impl std::error::Error for InconsistentPart {}
+
+impl InconsistentPart {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ur::fountain::InconsistentPart.code(), ur::ErrorCode(207));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(207)
+    }
+}
+
+/// The error returned by a complete [`Decoder`] when the padding of the last
+/// fragment, beyond the message length, isn't zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidPadding;
+
+impl std::fmt::Display for InvalidPadding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid padding detected")
+    }
+}
+
+impl std::error::Error for InvalidPadding {}
+
+impl InvalidPadding {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ur::fountain::InvalidPadding.code(), ur::ErrorCode(208));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(208)
+    }
+}
+
+/// The error returned by an [`Encoder`] for a zero maximum fragment length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeroFragmentLength;
+
+impl std::fmt::Display for ZeroFragmentLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected positive maximum fragment length")
+    }
+}
+
+impl std::error::Error for ZeroFragmentLength {}
+
+impl ZeroFragmentLength {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ur::fountain::ZeroFragmentLength.code(), ur::ErrorCode(209));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(209)
+    }
+}
+
 /// A decoder capable of receiving and recombining fountain-encoded transmissions.
 ///
+/// A clone forks the decoding state, e.g. to try a continuation of the
+/// transmission while keeping the state before it. Cloning copies all parts
+/// received so far, up to a few times the size of the message.
+///
 /// # Examples
 ///
 /// See the [`crate::fountain`] module documentation for an example.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Decoder {
     decoded: std::collections::HashMap<usize, Part>,
     received: std::collections::HashSet<Vec<usize>>,
@@ -232,11 +695,78 @@ pub struct Decoder {
     message_length: usize,
     checksum: u32,
     fragment_length: usize,
+    sequence_policy: SequencePolicy,
+    last_sequence: Option<(usize, u32)>,
+    chooser: FragmentChooser,
+    max_accepted_degree: Option<usize>,
+    conflict: Option<usize>,
+    shared_message: once_cell::sync::OnceCell<std::sync::Arc<[u8]>>,
+    // the total capacity of the index vectors in `received` and `buffer`
+    index_capacity: usize,
+    // the most parts buffered at once, which bounds the size of `buffer`
+    buffer_peak_len: usize,
+    peak_memory: usize,
 }
 
 impl Decoder {
+    /// Sets the [`SequencePolicy`] of the decoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Decoder, Encoder, SequencePolicy, SequenceRegression};
+    /// let mut decoder = Decoder::default().with_sequence_policy(SequencePolicy::Strict);
+    /// let mut encoder = Encoder::new(&"data".repeat(10).as_bytes(), 3).unwrap();
+    /// let first = encoder.next_part();
+    /// decoder.receive(encoder.next_part()).unwrap();
+    /// assert_eq!(
+    ///     decoder.receive(first).unwrap_err().downcast_ref(),
+    ///     Some(&SequenceRegression { last: 2, got: 1 })
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_sequence_policy(mut self, policy: SequencePolicy) -> Self {
+        self.sequence_policy = policy;
+        self
+    }
+
+    /// Sets the maximum number of fragments a received part may combine,
+    /// parts of a higher degree are rejected with a [`DegreeError`].
+    ///
+    /// By default, parts of any degree are accepted. The work to reduce a part
+    /// grows with its degree, and an attacker able to inject parts can pick
+    /// sequence numbers of maximal degree. For decoders exposed to such parts,
+    /// `expected_max_degree(sequence_count, 0.4)` is the recommended value,
+    /// see [`expected_max_degree`] for the frames it costs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{DegreeError, Decoder, Encoder};
+    /// let mut encoder = Encoder::new(&[0; 100], 10).unwrap();
+    /// let mut decoder = Decoder::default().with_max_accepted_degree(3);
+    /// while !decoder.complete() {
+    ///     if let Err(e) = decoder.receive(encoder.next_part()) {
+    ///         assert!(e.is::<DegreeError>());
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_max_accepted_degree(mut self, max_accepted_degree: usize) -> Self {
+        self.max_accepted_degree = Some(max_accepted_degree);
+        self
+    }
+
     /// Receives a fountain-encoded part into the decoder.
     ///
+    /// Returns whether the part was absorbed, which is not the case once the
+    /// decoder is complete, or if the fragments combined into the part have
+    /// been received or solved before, e.g. on an earlier pass over the message.
+    ///
+    /// Once complete, the decoder ignores all parts without checking them,
+    /// whether they belong to the decoded message or another one, until it is
+    /// [`clear`]ed.
+    ///
     /// # Examples
     ///
     /// See the [`crate::fountain`] module documentation for an example.
@@ -244,45 +774,146 @@ impl Decoder {
     /// # Errors
     ///
     /// If the part would fail [`validate`] because it is inconsistent
-    /// with previously received parts, an error will be returned.
+    /// with previously received parts, an [`InconsistentPart`] error will be
+    /// returned. If its sequence number or sequence count is zero, or its
+    /// fragments can't hold the message length, an error will be returned.
+    /// If the part claims an empty
+    /// message, an [`EmptyMessage`] error will be returned. If the part
+    /// combines more fragments than the maximum accepted degree, a
+    /// [`DegreeError`] will be returned. If the part solves an already solved
+    /// fragment with different data, a [`FragmentConflict`] will be returned.
+    /// If the part violates [`SequencePolicy::Strict`], a [`SequenceRegression`]
+    /// or [`ConflictingPart`] error will be returned.
     ///
     /// [`validate`]: Decoder::validate
+    /// [`clear`]: Decoder::clear
     pub fn receive(&mut self, part: Part) -> anyhow::Result<bool> {
+        let result = self.receive_part(part);
+        self.peak_memory = self.peak_memory.max(self.memory_estimate());
+        result
+    }
+
+    fn receive_part(&mut self, part: Part) -> anyhow::Result<bool> {
         if self.complete() {
             return Ok(false);
         }
+        if part.sequence == 0 || part.sequence_count == 0 {
+            anyhow::bail!("expected positive sequence number and sequence count")
+        }
+        if part.message_length == 0 {
+            return Err(EmptyMessage.into());
+        }
         if self.received.is_empty() {
+            // more fragments than needed are tolerated, as by the reference implementation
+            if part.sequence_count.saturating_mul(part.data.len()) < part.message_length {
+                anyhow::bail!(
+                    "{} fragments of {} bytes can't hold a message of {} bytes",
+                    part.sequence_count,
+                    part.data.len(),
+                    part.message_length
+                )
+            }
             self.sequence_count = part.sequence_count;
             self.message_length = part.message_length;
             self.checksum = part.checksum;
             self.fragment_length = part.data.len();
+            self.chooser = FragmentChooser::new(part.sequence_count, part.checksum);
         } else if !self.validate(&part) {
-            anyhow::bail!("part is inconsistent with previous ones")
+            return Err(InconsistentPart.into());
         }
-        let indexes = part.indexes();
+        if self.sequence_policy == SequencePolicy::Strict {
+            self.check_sequence(&part)?;
+        }
+        let mut indexes = self.chooser.choose(part.sequence);
+        if let Some(max_accepted_degree) = self.max_accepted_degree {
+            if indexes.len() > max_accepted_degree {
+                return Err(DegreeError {
+                    degree: indexes.len(),
+                    max_accepted_degree,
+                }
+                .into());
+            }
+        }
+        // parts of later passes over the message choose the same fragments again
+        indexes.sort_unstable();
         if self.received.contains(&indexes) {
             return Ok(false);
         }
-        self.received.insert(indexes);
-        if part.is_simple() {
-            self.process_simple(part)?;
+        if indexes.iter().all(|index| self.decoded.contains_key(index)) {
+            if let [index] = *indexes.as_slice() {
+                self.solve(index, part);
+            }
+            self.index_capacity += indexes.capacity();
+            self.received.insert(indexes);
+            return self
+                .conflict
+                .take()
+                .map_or(Ok(false), |index| Err(FragmentConflict { index }.into()));
+        }
+        let received = indexes.clone();
+        self.index_capacity += received.capacity();
+        self.received.insert(received);
+        if let [index] = *indexes.as_slice() {
+            self.solve(index, part);
         } else {
-            self.process_complex(part)?;
+            self.process_complex(part, indexes)?;
+        }
+        // a mixed part may have been reduced to a solved fragment as well
+        self.process_queue()?;
+        if let Some(index) = self.conflict.take() {
+            return Err(FragmentConflict { index }.into());
         }
         Ok(true)
     }
 
-    fn process_simple(&mut self, part: Part) -> anyhow::Result<()> {
-        let index = *part
-            .indexes()
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("expected item"))?;
-        self.decoded.insert(index, part.clone());
-        self.queue.push_back((index, part));
-        self.process_queue()?;
+    fn check_sequence(&mut self, part: &Part) -> anyhow::Result<()> {
+        let data_checksum = crate::crc32().checksum(&part.data);
+        if let Some((last, last_checksum)) = self.last_sequence {
+            if part.sequence < last {
+                return Err(SequenceRegression {
+                    last,
+                    got: part.sequence,
+                }
+                .into());
+            }
+            if part.sequence == last && data_checksum != last_checksum {
+                return Err(ConflictingPart {
+                    sequence: part.sequence,
+                }
+                .into());
+            }
+        }
+        self.last_sequence = Some((part.sequence, data_checksum));
         Ok(())
     }
 
+    // Records a solved fragment, or the first conflict with an earlier solution.
+    fn solve(&mut self, index: usize, part: Part) {
+        if let Some(solved) = self.decoded.get(&index) {
+            if solved.data != part.data && self.conflict.is_none() {
+                self.conflict = Some(index);
+            }
+        } else {
+            self.decoded.insert(index, part.clone());
+            self.queue.push_back((index, part));
+        }
+    }
+
+    // Buffers a mixed part, keeping the first one combining the same fragments.
+    fn buffer_mixed(&mut self, indexes: Vec<usize>, part: Part) {
+        if let Some(buffered) = self.buffer.get(&indexes) {
+            if buffered.data != part.data && self.conflict.is_none() {
+                self.conflict = indexes.first().copied();
+            }
+        } else {
+            self.index_capacity += indexes.capacity();
+            self.buffer.insert(indexes, part);
+            self.buffer_peak_len = self.buffer_peak_len.max(self.buffer.len());
+        }
+    }
+
+    // Reduces the buffered mixed parts by the queued solved fragments until
+    // the queue is empty, i.e. no buffered part can be reduced any further.
     fn process_queue(&mut self) -> anyhow::Result<()> {
         while !self.queue.is_empty() {
             let (index, simple) = self
@@ -292,14 +923,15 @@ impl Decoder {
             let to_process: Vec<Vec<usize>> = self
                 .buffer
                 .keys()
-                .filter(|&idxs| idxs.iter().any(|&idx| idx == index))
+                .filter(|&idxs| idxs.contains(&index))
                 .cloned()
                 .collect();
             for indexes in to_process {
-                let mut part = self
+                let (key, mut part) = self
                     .buffer
-                    .remove(&indexes)
+                    .remove_entry(&indexes)
                     .ok_or_else(|| anyhow::anyhow!("expected item"))?;
+                self.index_capacity -= key.capacity();
                 let mut new_indexes = indexes.clone();
                 let to_remove = indexes
                     .iter()
@@ -307,20 +939,17 @@ impl Decoder {
                     .ok_or_else(|| anyhow::anyhow!("expected item"))?;
                 new_indexes.remove(to_remove);
                 part.data = xor(&part.data, &simple.data);
-                if new_indexes.len() == 1 {
-                    self.decoded
-                        .insert(*new_indexes.first().unwrap(), part.clone());
-                    self.queue.push_back((*new_indexes.first().unwrap(), part));
+                if let [index] = *new_indexes.as_slice() {
+                    self.solve(index, part);
                 } else {
-                    self.buffer.insert(new_indexes, part);
+                    self.buffer_mixed(new_indexes, part);
                 }
             }
         }
         Ok(())
     }
 
-    fn process_complex(&mut self, mut part: Part) -> anyhow::Result<()> {
-        let mut indexes = part.indexes();
+    fn process_complex(&mut self, mut part: Part, mut indexes: Vec<usize>) -> anyhow::Result<()> {
         let to_remove: Vec<usize> = indexes
             .clone()
             .into_iter()
@@ -344,11 +973,10 @@ impl Decoder {
                     .data,
             );
         }
-        if indexes.len() == 1 {
-            self.decoded.insert(*indexes.first().unwrap(), part.clone());
-            self.queue.push_back((*indexes.first().unwrap(), part));
+        if let [index] = *indexes.as_slice() {
+            self.solve(index, part);
         } else {
-            self.buffer.insert(indexes, part);
+            self.buffer_mixed(indexes, part);
         }
         Ok(())
     }
@@ -363,6 +991,129 @@ impl Decoder {
         self.message_length != 0 && self.decoded.len() == self.sequence_count
     }
 
+    /// Discards all received parts to decode another message, keeping the
+    /// configuration of the decoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Decoder, Encoder};
+    /// let mut decoder = Decoder::default().with_max_accepted_degree(5);
+    /// for message in ["data", "more data"] {
+    ///     let mut encoder = Encoder::new(message.as_bytes(), 3).unwrap();
+    ///     while !decoder.complete() {
+    ///         decoder.receive(encoder.next_part()).unwrap();
+    ///     }
+    ///     assert_eq!(decoder.message().unwrap().as_deref(), Some(message.as_bytes()));
+    ///     decoder.clear();
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        *self = Self {
+            sequence_policy: self.sequence_policy,
+            max_accepted_degree: self.max_accepted_degree,
+            ..Self::default()
+        };
+    }
+
+    /// Returns an estimate of the most bytes the decoder has held allocated
+    /// at once, as of the end of each received part.
+    ///
+    /// The estimate covers the stored fragments, the indexes of the received
+    /// parts, the hash tables holding them and the buffer returned by
+    /// [`message_arc`]. It errs on the high side, but doesn't cover the
+    /// short-lived allocations made while receiving a part, which are in the
+    /// order of a few fragments. It is maintained with a few additions per
+    /// part and is reset by [`clear`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Decoder, Encoder};
+    /// let mut encoder = Encoder::new(&[0; 1000], 100).unwrap();
+    /// let mut decoder = Decoder::default();
+    /// assert_eq!(decoder.peak_memory_estimate(), 0);
+    /// while !decoder.complete() {
+    ///     decoder.receive(encoder.next_part()).unwrap();
+    /// }
+    /// assert!(decoder.peak_memory_estimate() > 1000);
+    /// ```
+    ///
+    /// [`message_arc`]: Decoder::message_arc
+    /// [`clear`]: Decoder::clear
+    #[must_use]
+    pub fn peak_memory_estimate(&self) -> usize {
+        // the shared message is built without updating the peak
+        self.peak_memory.max(self.memory_estimate())
+    }
+
+    // Estimates the bytes currently allocated, assuming the hash tables of
+    // the standard library. Their capacity shrinks as removed entries leave
+    // tombstones, so the sizes of the tables are derived from their lengths.
+    fn memory_estimate(&self) -> usize {
+        use std::mem::size_of;
+        // A table grows to the smallest power of two of buckets with 7 of 8
+        // of them holding its entries, plus a control byte per bucket and
+        // a group of them, and padding.
+        fn table_size(entries: usize, entry_size: usize) -> usize {
+            let buckets = match entries {
+                0 => return 0,
+                1..=3 => 4,
+                4..=7 => 8,
+                _ => (entries * 8 / 7).next_power_of_two(),
+            };
+            buckets * (entry_size + 1) + 32
+        }
+        let parts = self.decoded.len() + self.buffer.len() + self.queue.len();
+        parts * self.fragment_length
+            + self.index_capacity * size_of::<usize>()
+            // solved fragments and received indexes are never removed
+            + table_size(self.decoded.len(), size_of::<(usize, Part)>())
+            + table_size(self.received.len(), size_of::<Vec<usize>>())
+            // a table with tombstones grows when half full of entries
+            + table_size(
+                2 * self.buffer_peak_len,
+                size_of::<(Vec<usize>, Part)>(),
+            )
+            + self.queue.capacity() * size_of::<(usize, Part)>()
+            + self
+                .chooser
+                .degrees
+                .as_ref()
+                .map_or(0, crate::sampler::Weighted::heap_size)
+            + self
+                .shared_message
+                .get()
+                .map_or(0, |message| message.len() + 2 * size_of::<usize>())
+    }
+
+    // Returns the number of decoded segments and the total number of segments.
+    pub(crate) fn progress(&self) -> (usize, usize) {
+        (self.decoded.len(), self.sequence_count)
+    }
+
+    // Returns the message checksum, once a part has been received.
+    pub(crate) fn checksum(&self) -> Option<u32> {
+        (!self.received.is_empty()).then_some(self.checksum)
+    }
+
+    /// Returns the message segment with the given index, if it has been decoded.
+    ///
+    /// The segments are the fragments the message has been split up into by the
+    /// encoder, without the padding of the last one.
+    ///
+    /// # Examples
+    ///
+    /// See [`Encoder::from_fragments`] for an example.
+    #[must_use]
+    pub fn fragment(&self, index: usize) -> Option<&[u8]> {
+        let start = index.checked_mul(self.fragment_length)?;
+        let length = self.message_length.checked_sub(start)?;
+        self.decoded
+            .get(&index)
+            .and_then(|part| part.data.get(..length.min(self.fragment_length)))
+    }
+
     /// Checks whether a [`Part`] is receivable by the decoder.
     /// This can fail if other parts were previously received whose
     /// metadata (such as number of segments) is inconsistent with the
@@ -392,26 +1143,23 @@ impl Decoder {
     /// ```
     #[must_use]
     pub fn validate(&self, part: &Part) -> bool {
-        if part.sequence_count != self.sequence_count {
-            return false;
-        }
-        if part.message_length != self.message_length {
-            return false;
-        }
-        if part.checksum != self.checksum {
-            return false;
-        }
-        if part.data.len() != self.fragment_length {
-            return false;
-        }
-        true
+        part.metadata()
+            == Metadata {
+                sequence_count: self.sequence_count,
+                message_length: self.message_length,
+                checksum: self.checksum,
+                fragment_length: self.fragment_length,
+            }
     }
 
     /// If [`complete`], returns the decoded message, `None` otherwise.
     ///
     /// # Errors
     ///
-    /// If an inconsistent internal state is detected, an error will be returned.
+    /// If the decoded message doesn't match the checksum of the parts, a
+    /// [`MessageChecksum`] error will be returned. If the padding of the last
+    /// fragment isn't zero, an [`InvalidPadding`] error will be returned. If an
+    /// inconsistent internal state is detected, an error will be returned.
     ///
     /// # Examples
     ///
@@ -422,29 +1170,148 @@ impl Decoder {
         if !self.complete() {
             return Ok(None);
         }
-        let combined = (0..self.sequence_count)
-            .map(|idx| {
-                self.decoded
-                    .get(&idx)
-                    .ok_or_else(|| anyhow::anyhow!("expected item"))
-            })
-            .collect::<Result<Vec<&Part>, anyhow::Error>>()?
-            .iter()
-            .fold(vec![], |a, b| [a, b.data.clone()].concat());
-        if !combined
+        self.join_fragments().map(Some)
+    }
+
+    /// If [`complete`], returns the decoded message as a shared buffer, `None`
+    /// otherwise.
+    ///
+    /// The buffer is built on the first call, later calls return it again
+    /// without copying the message, which suits handing a large message to
+    /// several consumers. Clearing the decoder doesn't affect buffers which
+    /// have been returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`message`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Decoder, Encoder};
+    /// let mut encoder = Encoder::new("Ten chars!".as_bytes(), 4).unwrap();
+    /// let mut decoder = Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(encoder.next_part()).unwrap();
+    /// }
+    /// let message = decoder.message_arc().unwrap().unwrap();
+    /// assert_eq!(&*message, "Ten chars!".as_bytes());
+    /// let again = decoder.message_arc().unwrap().unwrap();
+    /// assert!(std::sync::Arc::ptr_eq(&message, &again));
+    /// ```
+    ///
+    /// [`complete`]: Decoder::complete
+    /// [`message`]: Decoder::message
+    pub fn message_arc(&self) -> anyhow::Result<Option<std::sync::Arc<[u8]>>> {
+        if !self.complete() {
+            return Ok(None);
+        }
+        self.shared_message
+            .get_or_try_init(|| self.join_fragments().map(std::sync::Arc::from))
+            .map(|message| Some(message.clone()))
+    }
+
+    // Joins the decoded fragments into the message and verifies it.
+    fn join_fragments(&self) -> anyhow::Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(self.sequence_count * self.fragment_length);
+        for index in 0..self.sequence_count {
+            let part = self
+                .decoded
+                .get(&index)
+                .ok_or_else(|| anyhow::anyhow!("expected item"))?;
+            message.extend_from_slice(&part.data);
+        }
+        if !message
             .get(self.message_length..)
             .ok_or_else(|| anyhow::anyhow!("expected item"))?
             .iter()
             .all(|&x| x == 0)
         {
-            anyhow::bail!("invalid padding detected")
+            return Err(InvalidPadding.into());
         }
-        Ok(Some(
-            combined
-                .get(..self.message_length)
-                .ok_or_else(|| anyhow::anyhow!("expected item"))?
-                .to_vec(),
-        ))
+        message.truncate(self.message_length);
+        let checksum = crate::crc32().checksum(&message);
+        if checksum != self.checksum {
+            return Err(MessageChecksum {
+                expected: self.checksum,
+                got: checksum,
+            }
+            .into());
+        }
+        Ok(message)
+    }
+}
+
+/// The properties of a message shared by all of its parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// The number of segments the message has been split up into.
+    pub sequence_count: usize,
+    /// The length of the message.
+    pub message_length: usize,
+    /// The CRC32 checksum of the message.
+    pub checksum: u32,
+    /// The length of the message segments.
+    pub fragment_length: usize,
+}
+
+/// The header of a [`Part`], i.e. everything but its fragment data,
+/// see [`crate::ur::UR::peek_part_header`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartHeader {
+    /// The sequence number of the part.
+    pub sequence: usize,
+    /// The properties of the message shared by all of its parts.
+    pub metadata: Metadata,
+}
+
+// The longest CBOR encoding of a part header: the array head, four integers
+// of up to five bytes and the head of the fragment of up to nine bytes.
+pub(crate) const MAX_HEADER_LENGTH: usize = 1 + 4 * 5 + 9;
+
+impl PartHeader {
+    // Parses the header from the leading bytes of a CBOR-encoded part,
+    // accepting the same integers as the deserialization of a part.
+    pub(crate) fn from_cbor_head(cbor: &[u8]) -> anyhow::Result<Self> {
+        let mut bytes = cbor.iter().copied();
+        let mut head = |major: u8, error: &str| -> anyhow::Result<u64> {
+            let mut next = || {
+                bytes
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("truncated part header"))
+            };
+            let initial = next()?;
+            anyhow::ensure!(initial >> 5 == major, "{}", error);
+            let length = match initial & 0x1f {
+                argument @ 0..=23 => return Ok(u64::from(argument)),
+                24 => 1,
+                25 => 2,
+                26 => 4,
+                27 => 8,
+                _ => anyhow::bail!("{}", error),
+            };
+            (0..length).try_fold(0, |argument, _| Ok(argument << 8 | u64::from(next()?)))
+        };
+        anyhow::ensure!(
+            head(4, "invalid top-level item")? == 5,
+            "invalid cbor array length"
+        );
+        let mut number = |index: usize| -> anyhow::Result<u32> {
+            let error = format!("unexpected item at position {index}");
+            u32::try_from(head(0, &error)?).map_err(|_| anyhow::anyhow!(error))
+        };
+        let (sequence, sequence_count, message_length, checksum) =
+            (number(0)?, number(1)?, number(2)?, number(3)?);
+        let fragment_length = head(2, "unexpected item at position 4")?;
+        Ok(Self {
+            sequence: sequence as usize,
+            metadata: Metadata {
+                sequence_count: sequence_count as usize,
+                message_length: message_length as usize,
+                checksum,
+                fragment_length: usize::try_from(fragment_length)?,
+            },
+        })
     }
 }
 
@@ -545,10 +1412,104 @@ impl std::fmt::Display for Part {
 }
 
 impl Part {
-    pub(crate) fn from_cbor(cbor: &[u8]) -> anyhow::Result<Self> {
+    /// Describes the CBOR serialization of a part, as produced by [`cbor`].
+    ///
+    /// Integers use the shortest encoding of their value, decoding accepts any
+    /// encoding of a value which fits into 32 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert!(ur::fountain::Part::CBOR_LAYOUT.starts_with("array(5)"));
+    /// ```
+    ///
+    /// [`cbor`]: Part::cbor
+    pub const CBOR_LAYOUT: &'static str = "array(5) [\
+        uint32 sequence, \
+        uint32 sequence_count, \
+        uint32 message_length, \
+        uint32 checksum, \
+        definite-length bytes data\
+    ]";
+
+    /// Decodes a part from its CBOR serialization, as produced by [`cbor`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Encoder, Part};
+    /// let mut encoder = Encoder::new("data".as_bytes(), 3).unwrap();
+    /// let part = encoder.next_part();
+    /// let decoded = Part::from_cbor(&part.cbor().unwrap()).unwrap();
+    /// assert_eq!(decoded.to_string(), part.to_string());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the input is not a well-formed CBOR serialization of a part,
+    /// an error will be returned.
+    ///
+    /// [`cbor`]: Part::cbor
+    pub fn from_cbor(cbor: &[u8]) -> anyhow::Result<Self> {
         Ok(serde_cbor::from_slice(cbor)?)
     }
 
+    /// Extracts the part carried by a multi-part uniform resource.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Encoder, Part};
+    /// let mut encoder = Encoder::new("data".as_bytes(), 3).unwrap();
+    /// let part = encoder.next_part();
+    /// let encoded = part.to_ur_string("bytes").unwrap();
+    /// let ur = ur::ur::UR::parse(&encoded).unwrap();
+    /// assert_eq!(Part::from_ur(&ur).unwrap().to_string(), part.to_string());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the uniform resource is single-part, its payload is not a well-formed
+    /// `bytewords` string, or the decoded payload is not a valid CBOR-encoded part,
+    /// an error will be returned.
+    pub fn from_ur(ur: &crate::ur::UR) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            ur.kind() == crate::ur::Kind::MultiPart,
+            "Tried to receive a single-part ur"
+        );
+        Self::from_cbor(&ur.decode_payload()?)
+    }
+
+    /// Encodes the part into a multi-part uniform resource of the given type.
+    ///
+    /// The format is `ur:<type>/<sequence>-<sequence count>/<payload>`, where the
+    /// payload is the minimal `bytewords` encoding of the [`cbor`] serialization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::Encoder;
+    /// let mut encoder = Encoder::new("data".as_bytes(), 5).unwrap();
+    /// assert_eq!(
+    ///     encoder.next_part().to_ur_string("bytes").unwrap(),
+    ///     "ur:bytes/1-1/lpadadaacypmwfwfiafyiehsjyhsmndyyngo"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails an error will be returned.
+    ///
+    /// [`cbor`]: Part::cbor
+    pub fn to_ur_string(&self, ur_type: &str) -> anyhow::Result<String> {
+        let body = crate::bytewords::encode(&self.cbor()?, &crate::bytewords::Style::Minimal);
+        Ok(crate::ur::join(&[
+            ur_type.to_string(),
+            self.sequence_id(),
+            body,
+        ]))
+    }
+
     /// Returns the indexes of the message segments that were combined into this part.
     ///
     /// # Examples
@@ -588,15 +1549,94 @@ impl Part {
         self.indexes().len() == 1
     }
 
-    pub(crate) fn cbor(&self) -> anyhow::Result<Vec<u8>> {
+    /// Serializes the part into CBOR.
+    ///
+    /// The part is encoded as a five-element array consisting of the sequence
+    /// number, the sequence count, the message length, the message checksum
+    /// and the fragment data as a byte string. The integers are encoded
+    /// as unsigned 32-bit values at most, in line with the reference implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::Encoder;
+    /// let mut encoder = Encoder::new("data".as_bytes(), 5).unwrap();
+    /// assert_eq!(
+    ///     encoder.next_part().cbor().unwrap(),
+    ///     vec![0x85, 0x01, 0x01, 0x04, 0x1a, 0xad, 0xf3, 0xf3, 0x63, 0x44, 0x64, 0x61, 0x74, 0x61]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails an error will be returned.
+    pub fn cbor(&self) -> anyhow::Result<Vec<u8>> {
         Ok(serde_cbor::to_vec(self)?)
     }
 
+    /// Returns the sequence number of the part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::Encoder;
+    /// let mut encoder = Encoder::new("data".as_bytes(), 3).unwrap();
+    /// encoder.next_part();
+    /// assert_eq!(encoder.next_part().sequence(), 2);
+    /// ```
+    #[must_use]
+    pub fn sequence(&self) -> usize {
+        self.sequence
+    }
+
+    /// Returns the number of segments the original message has been split up into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::Encoder;
+    /// let mut encoder = Encoder::new("data".as_bytes(), 3).unwrap();
+    /// assert_eq!(encoder.next_part().sequence_count(), 2);
+    /// ```
+    #[must_use]
+    pub fn sequence_count(&self) -> usize {
+        self.sequence_count
+    }
+
     #[must_use]
     pub(crate) fn sequence_id(&self) -> String {
         format!("{}-{}", self.sequence, self.sequence_count)
     }
 
+    /// Returns the properties of the message shared by all of its parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::fountain::{Encoder, Metadata};
+    /// let mut encoder = Encoder::new("Ten chars!".as_bytes(), 4).unwrap();
+    /// let metadata = encoder.next_part().metadata();
+    /// assert_eq!(
+    ///     metadata,
+    ///     Metadata {
+    ///         sequence_count: 3,
+    ///         message_length: 10,
+    ///         checksum: 0xf4eb_56f1,
+    ///         fragment_length: 4,
+    ///     }
+    /// );
+    /// assert_eq!(encoder.next_part().metadata(), metadata);
+    /// ```
+    #[must_use]
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            sequence_count: self.sequence_count,
+            message_length: self.message_length,
+            checksum: self.checksum,
+            fragment_length: self.data.len(),
+        }
+    }
+
     /// Returns a slice view onto the underlying data.
     ///
     /// Note that for non-simple parts this will be the result of
@@ -615,13 +1655,95 @@ impl Part {
     }
 }
 
+/// Returns the lowest degree which at most the given fraction of the mixed
+/// parts of a message split into the given number of fragments exceed.
+///
+/// The first `sequence_count` parts carry a single fragment each, the degree
+/// of any later, mixed part is `d` with a probability proportional to `1 / d`.
+/// This distribution is heavy-tailed: discarding one in a thousand mixed parts
+/// still accepts degrees close to the sequence count, which bounds the work
+/// of reducing a forged part hardly at all.
+///
+/// Discarding 40% of the mixed parts is the recommended fraction for a
+/// [`Decoder`] exposed to forged parts: it caps the degree at 50 for a
+/// thousand fragments, a twentieth of the work of a forged part of maximal
+/// degree. The price is only paid on lossy channels, where more mixed parts
+/// are needed to recover the lost fragments: a transfer of a thousand
+/// fragments losing every third frame takes up to a third more frames, and a
+/// few percent more on average. With fewer fragments the price grows,
+/// while forged parts get cheaper to reduce.
+///
+/// # Examples
+///
+/// ```
+/// use ur::fountain::expected_max_degree;
+/// assert_eq!(expected_max_degree(1000, 0.4), 50);
+/// assert_eq!(expected_max_degree(1000, 0.001), 993);
+/// ```
 #[must_use]
-#[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_precision_loss)]
-#[allow(clippy::cast_sign_loss)]
-pub(crate) fn fragment_length(data_length: usize, max_fragment_length: usize) -> usize {
-    let fragment_count = data_length / max_fragment_length + 1;
-    (data_length as f64 / fragment_count as f64).ceil() as usize
+pub fn expected_max_degree(sequence_count: usize, discarded: f64) -> usize {
+    let total: f64 = (1..=sequence_count).map(|d| 1.0 / d as f64).sum();
+    let mut tail = total;
+    for degree in 1..=sequence_count {
+        tail -= 1.0 / degree as f64;
+        if tail <= total * discarded {
+            return degree;
+        }
+    }
+    sequence_count
+}
+
+/// Returns the length of the fragments an [`Encoder`] splits a message of the
+/// given length into, or zero if either length is zero.
+///
+/// The message is split into the fewest fragments not exceeding the maximum
+/// length, as in the reference implementation, which are then made as even as possible.
+///
+/// # Examples
+///
+/// ```
+/// const FRAGMENT_LENGTH: usize = ur::fountain::fragment_length(1000, 300);
+/// assert_eq!(FRAGMENT_LENGTH, 250);
+/// ```
+#[must_use]
+pub const fn fragment_length(message_length: usize, max_fragment_length: usize) -> usize {
+    if message_length == 0 || max_fragment_length == 0 {
+        return 0;
+    }
+    (message_length - 1) / sequence_count(message_length, max_fragment_length) + 1
+}
+
+/// Returns the number of fragments an [`Encoder`] splits a message of the
+/// given length into, or zero if either length is zero.
+///
+/// # Examples
+///
+/// ```
+/// const SEQUENCE_COUNT: usize = ur::fountain::sequence_count(1000, 300);
+/// assert_eq!(SEQUENCE_COUNT, 4);
+/// ```
+#[must_use]
+pub const fn sequence_count(message_length: usize, max_fragment_length: usize) -> usize {
+    if message_length == 0 || max_fragment_length == 0 {
+        return 0;
+    }
+    (message_length - 1) / max_fragment_length + 1
+}
+
+/// Returns the length of a message of the given length padded with zeros
+/// to fill all of its fragments, or zero if either length is zero.
+///
+/// # Examples
+///
+/// ```
+/// const PADDED_LEN: usize = ur::fountain::padded_len(1001, 300);
+/// assert_eq!(PADDED_LEN, 1004);
+/// ```
+#[must_use]
+pub const fn padded_len(message_length: usize, max_fragment_length: usize) -> usize {
+    sequence_count(message_length, max_fragment_length)
+        * fragment_length(message_length, max_fragment_length)
 }
 
 #[must_use]
@@ -633,18 +1755,64 @@ pub(crate) fn partition(mut data: Vec<u8>, fragment_length: usize) -> Vec<Vec<u8
 
 #[must_use]
 fn choose_fragments(sequence: usize, fragment_count: usize, checksum: u32) -> Vec<usize> {
-    if sequence <= fragment_count {
-        return vec![sequence - 1];
-    }
-    #[allow(clippy::cast_possible_truncation)]
-    let mut seed: Vec<u8> = (sequence as u32).to_be_bytes().to_vec();
-    seed.extend((checksum as u32).to_be_bytes().to_vec());
-    let mut xoshiro = crate::xoshiro::Xoshiro256::from(seed.as_slice());
-    let degree = xoshiro.choose_degree(fragment_count);
-    let indexes = (0..fragment_count).collect();
-    let mut shuffled = xoshiro.shuffled(indexes);
-    shuffled.truncate(degree as usize);
-    shuffled
+    // parts don't belong to an encoder or decoder, so the chooser of the last
+    // message is kept per thread, which suits inspecting the parts of a message
+    thread_local! {
+        static CHOOSER: std::cell::RefCell<FragmentChooser> =
+            std::cell::RefCell::new(FragmentChooser::default());
+    }
+    CHOOSER.with(|chooser| {
+        let mut chooser = chooser.borrow_mut();
+        if chooser.fragment_count == fragment_count {
+            // the degree sampler only depends on the fragment count
+            chooser.checksum = checksum;
+        } else {
+            *chooser = FragmentChooser::new(fragment_count, checksum);
+        }
+        chooser.choose(sequence)
+    })
+}
+
+// Chooses the fragments combined into the parts of a message. The degree
+// sampler only depends on the fragment count and is built once per message.
+// Both the degree and the shuffle are drawn from the same seeded RNG, so the
+// chosen fragments depend on the degree distribution, not only on the seed.
+#[derive(Clone, Debug, Default)]
+struct FragmentChooser {
+    fragment_count: usize,
+    checksum: u32,
+    degrees: Option<crate::sampler::Weighted>,
+}
+
+impl FragmentChooser {
+    fn new(fragment_count: usize, checksum: u32) -> Self {
+        Self {
+            fragment_count,
+            checksum,
+            degrees: None,
+        }
+    }
+
+    fn choose(&mut self, sequence: usize) -> Vec<usize> {
+        if sequence <= self.fragment_count {
+            return vec![sequence - 1];
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let mut seed: Vec<u8> = (sequence as u32).to_be_bytes().to_vec();
+        seed.extend(self.checksum.to_be_bytes().to_vec());
+        let mut xoshiro = crate::xoshiro::Xoshiro256::from(seed.as_slice());
+        let fragment_count = self.fragment_count;
+        let degrees = self
+            .degrees
+            .get_or_insert_with(|| crate::sampler::Weighted::degrees(fragment_count));
+        let degree = xoshiro.choose_degree(degrees);
+        let indexes = (0..fragment_count).collect();
+        let mut shuffled = xoshiro.shuffled(indexes);
+        shuffled.truncate(degree as usize);
+        // the decoder keeps the indexes of every received part
+        shuffled.shrink_to_fit();
+        shuffled
+    }
 }
 
 #[must_use]
@@ -660,6 +1828,156 @@ mod tests {
     fn test_fragment_length() {
         assert_eq!(fragment_length(12345, 1955), 1764);
         assert_eq!(fragment_length(12345, 30000), 12345);
+        assert_eq!(fragment_length(1, 10), 1);
+        assert_eq!(fragment_length(10, 10), 10);
+        assert_eq!(fragment_length(11, 10), 6);
+        assert_eq!(fragment_length(20, 10), 10);
+        assert_eq!(fragment_length(0, 10), 0);
+        assert_eq!(fragment_length(10, 0), 0);
+    }
+
+    // evaluated at compile time
+    const _: () = assert!(fragment_length(12345, 1955) == 1764);
+    const _: () = assert!(sequence_count(12345, 1955) == 7);
+    const _: () = assert!(padded_len(12345, 1955) == 12348);
+    const _: () = assert!(sequence_count(10, 10) == 1 && padded_len(10, 10) == 10);
+    const _: () = assert!(sequence_count(0, 10) == 0 && padded_len(10, 0) == 0);
+
+    #[test]
+    fn test_fragment_math() {
+        for message_length in 1..300 {
+            for max_fragment_length in [1, 2, 7, 10, 64, 100, 299, 300] {
+                let message = vec![0; message_length];
+                let mut encoder = Encoder::new(&message, max_fragment_length).unwrap();
+                let part = encoder.next_part();
+                assert_eq!(
+                    part.data.len(),
+                    fragment_length(message_length, max_fragment_length)
+                );
+                assert_eq!(
+                    encoder.fragment_count(),
+                    sequence_count(message_length, max_fragment_length)
+                );
+                assert_eq!(
+                    part.data.len() * encoder.fragment_count(),
+                    padded_len(message_length, max_fragment_length)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_multiple_fragmentation() {
+        for max_fragment_length in [1, 2, 3, 7, 16, 100] {
+            for message_length in 1..=4 * max_fragment_length {
+                // the fewest fragments not exceeding the maximum length, and the
+                // shortest fragment length for which that many hold the message
+                let mut count = 1;
+                while count * max_fragment_length < message_length {
+                    count += 1;
+                }
+                let mut length = 1;
+                while count * length < message_length {
+                    length += 1;
+                }
+                assert!((count - 1) * length < message_length, "empty fragment");
+
+                let seed = format!("multiple-{max_fragment_length}-{message_length}");
+                let message = crate::xoshiro::test_utils::make_message(&seed, message_length);
+                let mut padded = message.clone();
+                padded.resize(count * length, 0);
+                let mut encoder = Encoder::new(&message, max_fragment_length).unwrap();
+                let mut reader_encoder =
+                    Encoder::from_reader(message.as_slice(), message_length, max_fragment_length)
+                        .unwrap();
+                assert_eq!(encoder.fragment_count(), count);
+                assert_eq!(reader_encoder.fragment_count(), count);
+                assert_eq!(
+                    padded_len(message_length, max_fragment_length),
+                    padded.len()
+                );
+                let mut decoder = Decoder::default();
+                for fragment in padded.chunks(length) {
+                    let part = encoder.next_part();
+                    assert_eq!(part.data(), fragment);
+                    assert_eq!(
+                        reader_encoder.next_part().cbor().unwrap(),
+                        part.cbor().unwrap()
+                    );
+                    decoder.receive(part).unwrap();
+                }
+                assert_eq!(decoder.message().unwrap(), Some(message));
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder_fragment_count_mismatch() {
+        let message = b"Ten chars!";
+        let checksum = crate::crc32().checksum(message);
+        let parts = |sequence_count: usize| {
+            let mut padded = message.to_vec();
+            padded.resize(sequence_count * 4, 0);
+            padded
+                .chunks(4)
+                .enumerate()
+                .map(|(index, data)| Part {
+                    sequence: index + 1,
+                    sequence_count,
+                    message_length: message.len(),
+                    checksum,
+                    data: data.to_vec(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // an extra fragment of padding only is tolerated
+        let mut decoder = Decoder::default();
+        for part in parts(4) {
+            decoder.receive(part).unwrap();
+        }
+        assert_eq!(decoder.message().unwrap().as_deref(), Some(&message[..]));
+
+        // too few fragments are rejected on the first part
+        let mut decoder = Decoder::default();
+        assert_eq!(
+            decoder
+                .receive(parts(2).swap_remove(0))
+                .unwrap_err()
+                .to_string(),
+            "2 fragments of 4 bytes can't hold a message of 10 bytes"
+        );
+        assert!(!decoder.complete());
+    }
+
+    #[test]
+    fn test_reference_fragment_lengths() {
+        // parts of the reference implementation for a maximum fragment length of 10
+        for (message, parts) in [
+            ("a", vec!["850101011ae8b7be434161"]),
+            (
+                "Ten chars!",
+                vec!["8501010a1af4eb56f14a54656e20636861727321"],
+            ),
+            (
+                "Ten chars!x",
+                vec![
+                    "8501020b1a46923f5f4654656e206368",
+                    "8502020b1a46923f5f46617273217800",
+                ],
+            ),
+        ] {
+            let mut encoder = Encoder::new(message.as_bytes(), 10).unwrap();
+            let mut decoder = Decoder::default();
+            for part in &parts {
+                assert_eq!(hex::encode(encoder.next_part().cbor().unwrap()), *part);
+                decoder
+                    .receive(Part::from_cbor(&hex::decode(part).unwrap()).unwrap())
+                    .unwrap();
+            }
+            assert_eq!(encoder.fragment_count(), parts.len());
+            assert_eq!(decoder.message().unwrap().unwrap(), message.as_bytes());
+        }
     }
 
     #[test]
@@ -745,6 +2063,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fragment_chooser_cache() {
+        // the selection before caching, building the degree sampler for every part
+        #[allow(clippy::cast_precision_loss)]
+        fn uncached(sequence: usize, fragment_count: usize, checksum: u32) -> Vec<usize> {
+            if sequence <= fragment_count {
+                return vec![sequence - 1];
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            let mut seed: Vec<u8> = (sequence as u32).to_be_bytes().to_vec();
+            seed.extend(checksum.to_be_bytes().to_vec());
+            let mut xoshiro = crate::xoshiro::Xoshiro256::from(seed.as_slice());
+            let weights = (1..=fragment_count).map(|x| 1.0 / x as f64).collect();
+            let mut sampler = crate::sampler::Weighted::new(weights).unwrap();
+            let degree = xoshiro.choose_degree(&mut sampler);
+            let mut shuffled = xoshiro.shuffled((0..fragment_count).collect());
+            shuffled.truncate(degree as usize);
+            shuffled
+        }
+        for fragment_count in [1, 2, 11, 500] {
+            for checksum in [0x1234_5678, 0x8765_4321] {
+                let mut chooser = FragmentChooser::new(fragment_count, checksum);
+                for sequence in 1..=1000 {
+                    let expected = uncached(sequence, fragment_count, checksum);
+                    assert_eq!(chooser.choose(sequence), expected);
+                    // alternating messages replace the per-thread chooser
+                    assert_eq!(
+                        choose_fragments(sequence, fragment_count, checksum),
+                        expected
+                    );
+                    assert_eq!(
+                        choose_fragments(sequence, 7, checksum),
+                        uncached(sequence, 7, checksum)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fragment_chooser_depends_on_distribution() {
+        // a distribution favouring low degrees, as some links with little loss
+        // would want, derives different fragments from the same seeds
+        let fragment_count = 20;
+        let mut standard = FragmentChooser::new(fragment_count, 0x1234_5678);
+        let mut low_degree = FragmentChooser::new(fragment_count, 0x1234_5678);
+        #[allow(clippy::cast_precision_loss)]
+        let weights = (1..=fragment_count)
+            .map(|degree| (degree as f64).powi(-3))
+            .collect();
+        low_degree.degrees = Some(crate::sampler::Weighted::new(weights).unwrap());
+        let differing = (fragment_count + 1..=fragment_count + 100)
+            .filter(|&sequence| standard.choose(sequence) != low_degree.choose(sequence))
+            .count();
+        assert!(differing > 50, "only {differing} of 100 parts differ");
+    }
+
     #[test]
     fn test_xor() {
         let mut rng = crate::xoshiro::Xoshiro256::from("Wolf");
@@ -823,14 +2198,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_part_cbor_conformance() {
+        // the first parts of the multi-part example of the reference implementation
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 256);
+        let mut encoder = Encoder::new(&message, 30).unwrap();
+        for (sequence, expected) in [
+            (1, "8501091901001a0167aa07581d916ec65cf77cadf55cd7f9cda1a1030026ddd42e905b77adc36e4f2d3c"),
+            (9, "8509091901001a0167aa07581d951e65305b56a3706e3e86eb01c803bbf915d80edcd64d4d0000000000"),
+            (10, "850a091901001a0167aa07581d330f0f33a05eead4f331df229871bee733b50de71afd2e5a79f196de09"),
+        ] {
+            let cbor = hex::decode(expected).unwrap();
+            let part = Part::from_cbor(&cbor).unwrap();
+            assert_eq!(part.sequence, sequence);
+            assert_eq!(part.sequence_count, 9);
+            assert_eq!(part.message_length, 256);
+            assert_eq!(part.checksum, 0x0167_aa07);
+            assert_eq!(part.checksum, crate::crc32().checksum(&message));
+            assert_eq!(part.data.len(), 29);
+            assert_eq!(part.cbor().unwrap(), cbor);
+            while encoder.current_sequence() < sequence - 1 {
+                encoder.next_part();
+            }
+            assert_eq!(encoder.next_part().cbor().unwrap(), cbor);
+        }
+        // the largest values of the 32-bit fields use their five-byte encoding
+        let part = Part {
+            sequence: u32::MAX as usize,
+            sequence_count: u32::MAX as usize,
+            message_length: u32::MAX as usize,
+            checksum: u32::MAX,
+            data: vec![],
+        };
+        assert_eq!(
+            hex::encode(part.cbor().unwrap()),
+            "851affffffff1affffffff1affffffff1affffffff40"
+        );
+    }
+
     #[test]
     fn test_fountain_encoder_zero_max_length() {
+        let error = Encoder::new("foo".as_bytes(), 0).unwrap_err();
+        assert!(error.is::<ZeroFragmentLength>());
         assert_eq!(
-            Encoder::new("foo".as_bytes(), 0).unwrap_err().to_string(),
+            error.to_string(),
             "expected positive maximum fragment length"
         );
     }
 
+    #[test]
+    fn test_fountain_encoder_from_fragments() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 4 * 64 + 10);
+        let fragments: Vec<&[u8]> = message.chunks(64).collect();
+        let mut encoder = Encoder::from_fragments(&fragments).unwrap();
+        assert_eq!(encoder.fragment_count(), 5);
+        let mut decoder = Decoder::default();
+        let mut skip = false;
+        while !decoder.complete() {
+            let part = encoder.next_part();
+            if !skip {
+                decoder.receive(part).unwrap();
+            }
+            skip = !skip;
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message.clone()));
+        for (index, fragment) in fragments.iter().enumerate() {
+            assert_eq!(decoder.fragment(index), Some(*fragment));
+        }
+        assert_eq!(decoder.fragment(5), None);
+
+        // fragments matching the default split yield the same parts
+        let mut encoder = Encoder::from_fragments(&message.chunks(54).collect::<Vec<_>>()).unwrap();
+        let mut expected = Encoder::new(&message, 60).unwrap();
+        for _ in 0..20 {
+            assert_eq!(
+                encoder.next_part().cbor().unwrap(),
+                expected.next_part().cbor().unwrap()
+            );
+        }
+
+        assert_eq!(
+            Encoder::from_fragments(&[]).unwrap_err().to_string(),
+            "expected non-empty message"
+        );
+        assert_eq!(
+            Encoder::from_fragments(&[b"", b"ab"])
+                .unwrap_err()
+                .to_string(),
+            "expected non-empty message"
+        );
+        assert_eq!(
+            Encoder::from_fragments(&[b"ab", b"c", b"d"])
+                .unwrap_err()
+                .to_string(),
+            "fragment 1 has length 1, expected 2"
+        );
+        assert_eq!(
+            Encoder::from_fragments(&[b"ab", b"cde"])
+                .unwrap_err()
+                .to_string(),
+            "fragment 1 has length 3, expected 2"
+        );
+        assert_eq!(
+            Encoder::from_fragments(&[b"ab", b""])
+                .unwrap_err()
+                .to_string(),
+            "fragment 1 has length 0, expected 2"
+        );
+    }
+
     #[test]
     fn test_fountain_encoder_is_complete() {
         let message = crate::xoshiro::test_utils::make_message("Wolf", 256);
@@ -883,6 +2359,99 @@ mod tests {
         assert_eq!(decoder.message().unwrap(), Some(message));
     }
 
+    // Receives a random subset of the emitted parts until the decoder completes
+    // and returns the number of parts the decoder needed.
+    fn decode_random_subset(seed: &str, message: &[u8], max_fragment_length: usize) -> usize {
+        let mut rng = crate::xoshiro::Xoshiro256::from(seed);
+        let mut encoder = Encoder::new(message, max_fragment_length).unwrap();
+        let mut decoder = Decoder::default();
+        let drop_percentage = rng.next_int(0, 50);
+        let mut received = 0;
+        while !decoder.complete() {
+            let part = encoder.next_part();
+            assert!(
+                encoder.current_sequence() < 100 * encoder.fragment_count() + 100,
+                "seed {seed}: decoder did not complete"
+            );
+            if rng.next_int(1, 100) > drop_percentage {
+                received += 1;
+                decoder.receive(part).unwrap();
+            }
+        }
+        assert_eq!(
+            decoder.message().unwrap().as_deref(),
+            Some(message),
+            "seed {seed}: decoded message differs"
+        );
+        received
+    }
+
+    #[test]
+    fn test_decoder_random_subsets() {
+        let runs = 100;
+        let mut overhead = 0.0;
+        for run in 0..runs {
+            let seed = format!("subset-{run}");
+            let mut rng = crate::xoshiro::Xoshiro256::from(seed.as_str());
+            #[allow(clippy::cast_possible_truncation)]
+            let message_length = rng.next_int(1, 1000) as usize;
+            #[allow(clippy::cast_possible_truncation)]
+            let max_fragment_length = rng.next_int(1, 100) as usize;
+            let message = rng.next_bytes(message_length);
+            let received = decode_random_subset(&seed, &message, max_fragment_length);
+            let fragment_count = Encoder::new(&message, max_fragment_length)
+                .unwrap()
+                .fragment_count();
+            #[allow(clippy::cast_precision_loss)]
+            let ratio = received as f64 / fragment_count as f64;
+            overhead += ratio;
+        }
+        overhead /= f64::from(runs);
+        assert!(overhead < 1.8, "average overhead {overhead}");
+    }
+
+    #[test]
+    fn test_decoder_single_byte_final_fragment() {
+        for fragment_length in 1..50 {
+            for fragment_count in 1..10 {
+                let seed = format!("single-{fragment_length}-{fragment_count}");
+                let message = crate::xoshiro::test_utils::make_message(
+                    &seed,
+                    fragment_length * (fragment_count - 1) + 1,
+                );
+                decode_random_subset(&seed, &message, fragment_length);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder_clear() {
+        let mut decoder = Decoder::default()
+            .with_sequence_policy(SequencePolicy::Strict)
+            .with_max_accepted_degree(2);
+        let mut encoder = Encoder::new(&[1; 100], 10).unwrap();
+        for _ in 0..3 {
+            decoder.receive(encoder.next_part()).unwrap();
+        }
+        decoder.clear();
+        assert!(!decoder.complete());
+        assert_eq!(decoder.checksum(), None);
+        // the configuration is kept
+        let mut other = Encoder::new(&[2; 50], 10).unwrap();
+        let first = other.next_part();
+        decoder.receive(other.next_part()).unwrap();
+        assert!(decoder.receive(first).is_err());
+        while !decoder.complete() {
+            if let Err(e) = decoder.receive(other.next_part()) {
+                assert!(e.is::<DegreeError>());
+            }
+        }
+        assert_eq!(decoder.message().unwrap(), Some(vec![2; 50]));
+        // once complete, parts of any message are ignored
+        assert!(!decoder.receive(encoder.next_part()).unwrap());
+        assert_eq!(decoder.message().unwrap(), Some(vec![2; 50]));
+    }
+
     #[test]
     fn test_decoder_receive_return_value() {
         let seed = "Wolf";
@@ -899,10 +2468,9 @@ mod tests {
         // non-valid
         let mut part = encoder.next_part();
         part.checksum += 1;
-        assert_eq!(
-            decoder.receive(part).unwrap_err().to_string(),
-            "part is inconsistent with previous ones"
-        );
+        let error = decoder.receive(part).unwrap_err();
+        assert!(error.is::<InconsistentPart>());
+        assert_eq!(error.to_string(), "part is inconsistent with previous ones");
         // decoder complete
         while !decoder.complete() {
             let part = encoder.next_part();
@@ -912,6 +2480,368 @@ mod tests {
         assert!(!decoder.receive(part).unwrap());
     }
 
+    #[test]
+    fn test_decoder_sequence_policy() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 100);
+        let mut encoder = Encoder::new(&message, 10).unwrap();
+        let parts: Vec<Part> = (0..20).map(|_| encoder.next_part()).collect();
+
+        // the default policy accepts regressions
+        let mut decoder = Decoder::default();
+        decoder.receive(parts.get(3).unwrap().clone()).unwrap();
+        decoder.receive(parts.get(1).unwrap().clone()).unwrap();
+
+        let mut decoder = Decoder::default().with_sequence_policy(SequencePolicy::Strict);
+        decoder.receive(parts.get(3).unwrap().clone()).unwrap();
+        // an injected part replaying an earlier sequence number
+        let error = decoder.receive(parts.get(1).unwrap().clone()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref(),
+            Some(&SequenceRegression { last: 4, got: 2 })
+        );
+        assert_eq!(error.to_string(), "sequence regression: last 4, got 2");
+        // an exact repetition is harmless
+        assert!(!decoder.receive(parts.get(3).unwrap().clone()).unwrap());
+        // an injected part with a repeated sequence number but different data
+        let mut forged = parts.get(3).unwrap().clone();
+        *forged.data.first_mut().unwrap() ^= 1;
+        let error = decoder.receive(forged).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&ConflictingPart { sequence: 4 }));
+        assert_eq!(error.to_string(), "conflicting part for sequence 4");
+        // the rejected parts didn't affect the decoder
+        for part in parts.iter().skip(4) {
+            decoder.receive(part.clone()).unwrap();
+        }
+        let mut remaining = parts.into_iter().skip(20);
+        while !decoder.complete() {
+            decoder
+                .receive(remaining.next().unwrap_or_else(|| encoder.next_part()))
+                .unwrap();
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message));
+    }
+
+    #[test]
+    fn test_decoder_zero_sequence() {
+        let mut decoder = Decoder::default();
+        let part = Part::from_cbor(&hex::decode("850200041aadf3f3634464617461").unwrap()).unwrap();
+        assert_eq!(
+            decoder.receive(part).unwrap_err().to_string(),
+            "expected positive sequence number and sequence count"
+        );
+        let part = Part::from_cbor(&hex::decode("850001041aadf3f3634464617461").unwrap()).unwrap();
+        assert_eq!(
+            decoder.receive(part).unwrap_err().to_string(),
+            "expected positive sequence number and sequence count"
+        );
+    }
+
+    #[test]
+    fn test_decoder_max_accepted_degree() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 200);
+        let mut encoder = Encoder::new(&message, 10).unwrap();
+        let mut forged = encoder.next_part();
+        forged.sequence = (21..)
+            .find(|&sequence| choose_fragments(sequence, 20, forged.checksum).len() == 20)
+            .unwrap();
+        let mut decoder = Decoder::default().with_max_accepted_degree(10);
+        let e = decoder.receive(forged.clone()).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<DegreeError>(),
+            Some(&DegreeError {
+                degree: 20,
+                max_accepted_degree: 10
+            })
+        );
+        assert_eq!(
+            e.to_string(),
+            "part degree 20 exceeds maximum accepted degree 10"
+        );
+        while !decoder.complete() {
+            let part = encoder.next_part();
+            let accepted = decoder.receive(part.clone()).is_ok();
+            assert_eq!(accepted, part.indexes().len() <= 10);
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message));
+
+        let mut decoder =
+            Decoder::default().with_max_accepted_degree(expected_max_degree(20, 0.001));
+        assert!(decoder.receive(forged).unwrap());
+
+        assert_eq!(expected_max_degree(0, 0.5), 0);
+        assert_eq!(expected_max_degree(1, 0.5), 1);
+        assert_eq!(expected_max_degree(100_000, 0.001), 98_799);
+    }
+
+    #[test]
+    fn test_decoder_recommended_max_accepted_degree() {
+        // every third frame is lost, the capped decoder takes up to a third
+        // more frames, and a few percent more in total
+        let max_accepted_degree = expected_max_degree(1000, 0.4);
+        assert_eq!(max_accepted_degree, 50);
+        let frames = |message: &[u8], decoder: &mut Decoder| {
+            let mut encoder = Encoder::new(message, 10).unwrap();
+            let mut frames = 0;
+            while !decoder.complete() {
+                let part = encoder.next_part();
+                frames += 1;
+                if frames % 3 != 0 {
+                    let _ = decoder.receive(part);
+                }
+            }
+            assert_eq!(decoder.message().unwrap().as_deref(), Some(message));
+            frames
+        };
+        let (mut total, mut total_capped) = (0, 0);
+        for seed in ["Wolf", "Fox", "Bear", "Lynx"] {
+            let message = crate::xoshiro::test_utils::make_message(seed, 10_000);
+            let uncapped = frames(&message, &mut Decoder::default());
+            let capped = frames(
+                &message,
+                &mut Decoder::default().with_max_accepted_degree(max_accepted_degree),
+            );
+            assert!(
+                3 * capped <= 4 * uncapped,
+                "{capped} frames, {uncapped} uncapped"
+            );
+            total += uncapped;
+            total_capped += capped;
+        }
+        assert!(
+            10 * total_capped <= 11 * total,
+            "{total_capped} frames, {total} uncapped"
+        );
+    }
+
+    #[test]
+    fn test_decoder_fragment_conflict() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 100);
+        let mut encoder = Encoder::new(&message, 10).unwrap();
+        let mut corrupted = encoder.next_part();
+        corrupted.data[3] ^= 1;
+        for _ in 0..5 {
+            encoder.next_part();
+        }
+        let mut decoder = Decoder::default();
+        // solve the first fragment from mixed parts
+        while decoder.fragment(0).is_none() {
+            decoder.receive(encoder.next_part()).unwrap();
+        }
+        assert!(!decoder.complete());
+        let e = decoder.receive(corrupted).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<FragmentConflict>(),
+            Some(&FragmentConflict { index: 0 })
+        );
+        assert_eq!(e.to_string(), "fragment 0 solved twice with different data");
+        // the first solution is kept
+        while !decoder.complete() {
+            decoder.receive(encoder.next_part()).unwrap();
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message));
+    }
+
+    #[test]
+    fn test_decoder_duplicate_sets() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 100);
+        let mut encoder = Encoder::new(&message, 10).unwrap();
+        let parts: Vec<Part> = (0..200).map(|_| encoder.next_part()).collect();
+        let sorted = |part: &Part| {
+            let mut indexes = part.indexes();
+            indexes.sort_unstable();
+            indexes
+        };
+
+        // a mixed part of degree one repeats a pure part
+        let repeat = parts
+            .iter()
+            .skip(10)
+            .find(|part| part.indexes().len() == 1)
+            .unwrap();
+        let mut decoder = Decoder::default();
+        let pure = parts.get(*repeat.indexes().first().unwrap()).unwrap();
+        assert!(decoder.receive(pure.clone()).unwrap());
+        assert!(!decoder.receive(repeat.clone()).unwrap());
+
+        // a mixed part of solved fragments only
+        let mut decoder = Decoder::default();
+        for part in parts.iter().take(9) {
+            assert!(decoder.receive(part.clone()).unwrap());
+        }
+        let solved = parts
+            .iter()
+            .skip(10)
+            .find(|part| part.indexes().len() > 1 && !part.indexes().contains(&9))
+            .unwrap();
+        assert!(!decoder.receive(solved.clone()).unwrap());
+
+        // a mixed part reduced to the fragments of another one with different data
+        let (pair, mut triple, extra) = parts
+            .iter()
+            .skip(10)
+            .filter(|pair| pair.indexes().len() == 2)
+            .find_map(|pair| {
+                let indexes = sorted(pair);
+                parts.iter().skip(10).find_map(|triple| {
+                    let extra: Vec<usize> = sorted(triple)
+                        .into_iter()
+                        .filter(|index| !indexes.contains(index))
+                        .collect();
+                    (triple.indexes().len() == 3 && extra.len() == 1)
+                        .then(|| (pair.clone(), triple.clone(), *extra.first().unwrap()))
+                })
+            })
+            .unwrap();
+        triple.data[0] ^= 1;
+        let mut decoder = Decoder::default();
+        assert!(decoder.receive(pair.clone()).unwrap());
+        assert!(decoder.receive(triple).unwrap());
+        let e = decoder
+            .receive(parts.get(extra).unwrap().clone())
+            .unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<FragmentConflict>(),
+            Some(&FragmentConflict {
+                index: *sorted(&pair).first().unwrap()
+            })
+        );
+        // the first part is kept
+        for part in parts.iter().take(10) {
+            decoder.receive(part.clone()).unwrap();
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message));
+    }
+
+    #[test]
+    fn test_decoder_reception_order() {
+        let mut encoder = Encoder::new("Ten chars!".as_bytes(), 4).unwrap();
+        let parts: Vec<Part> = (0..25).map(|_| encoder.next_part()).collect();
+        let part = |sequence: usize| parts.get(sequence - 1).unwrap().clone();
+        assert_eq!(part(7).indexes().len(), 2);
+        assert_eq!(part(25).indexes().len(), 2);
+        // part 7 combines fragments 0 and 1, part 25 fragments 1 and 2
+        for order in [[7, 1, 25], [25, 1, 7], [1, 25, 7]] {
+            let mut decoder = Decoder::default();
+            for sequence in order {
+                decoder.receive(part(sequence)).unwrap();
+            }
+            assert_eq!(
+                decoder.message().unwrap().as_deref(),
+                Some("Ten chars!".as_bytes()),
+                "order {order:?}"
+            );
+        }
+
+        // any order of a sufficient set of mixed parts completes
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 1000);
+        let mut encoder = Encoder::new(&message, 20).unwrap();
+        for _ in 0..encoder.fragment_count() {
+            encoder.next_part();
+        }
+        let mut decoder = Decoder::default();
+        let mut parts = Vec::new();
+        while !decoder.complete() {
+            let part = encoder.next_part();
+            if decoder.receive(part.clone()).unwrap() {
+                parts.push(part);
+            }
+        }
+        let mut rng = crate::xoshiro::Xoshiro256::from("order");
+        for _ in 0..200 {
+            let mut decoder = Decoder::default();
+            for part in rng.shuffled(parts.clone()) {
+                decoder.receive(part).unwrap();
+            }
+            assert_eq!(decoder.message().unwrap().as_ref(), Some(&message));
+        }
+    }
+
+    #[test]
+    fn test_encoder_from_reader() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 1000);
+        for max_fragment_length in [1, 7, 100, 1000, 2000] {
+            let mut encoder =
+                Encoder::from_reader(message.as_slice(), 1000, max_fragment_length).unwrap();
+            let mut expected = Encoder::new(&message, max_fragment_length).unwrap();
+            for _ in 0..20 {
+                assert_eq!(
+                    encoder.next_part().cbor().unwrap(),
+                    expected.next_part().cbor().unwrap()
+                );
+            }
+        }
+        // only the message length is read
+        let mut reader = message.as_slice();
+        Encoder::from_reader(&mut reader, 10, 4).unwrap();
+        assert_eq!(reader.len(), 990);
+        assert_eq!(
+            Encoder::from_reader(&message[..15], 20, 4)
+                .unwrap_err()
+                .to_string(),
+            "message ended after 15 of 20 bytes"
+        );
+        assert!(Encoder::from_reader(message.as_slice(), 0, 4)
+            .unwrap_err()
+            .is::<EmptyMessage>());
+        assert_eq!(
+            Encoder::from_reader(message.as_slice(), 10, 0)
+                .unwrap_err()
+                .to_string(),
+            "expected positive maximum fragment length"
+        );
+    }
+
+    #[test]
+    fn test_decoder_message_checksum() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 100);
+        let mut encoder = Encoder::new(&message, 10).unwrap();
+        let checksum = crate::crc32().checksum(&message);
+        let mut decoder = Decoder::default();
+        while !decoder.complete() {
+            let mut part = encoder.next_part();
+            part.checksum = checksum ^ 1;
+            decoder.receive(part).unwrap();
+        }
+        let e = decoder.message().unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<MessageChecksum>(),
+            Some(&MessageChecksum {
+                expected: checksum ^ 1,
+                got: checksum
+            })
+        );
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "message checksum mismatch: expected {:08x}, got {checksum:08x}",
+                checksum ^ 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_empty_and_single_byte_messages() {
+        let e = Encoder::new(&[], 10).unwrap_err();
+        assert_eq!(e.downcast_ref::<EmptyMessage>(), Some(&EmptyMessage));
+        assert_eq!(e.to_string(), "expected non-empty message");
+        assert!(Encoder::from_fragments(&[])
+            .unwrap_err()
+            .is::<EmptyMessage>());
+
+        let mut encoder = Encoder::new(&[7], 10).unwrap();
+        let mut part = encoder.next_part();
+        assert_eq!(part.data(), [7]);
+        let mut decoder = Decoder::default();
+        assert!(decoder.receive(part.clone()).unwrap());
+        assert_eq!(decoder.message().unwrap(), Some(vec![7]));
+
+        part.message_length = 0;
+        let mut decoder = Decoder::default();
+        let e = decoder.receive(part).unwrap_err();
+        assert_eq!(e.downcast_ref::<EmptyMessage>(), Some(&EmptyMessage));
+        assert!(!decoder.complete());
+    }
+
     #[test]
     fn test_decoder_part_validation() {
         let mut encoder = Encoder::new("foo".as_bytes(), 2).unwrap();
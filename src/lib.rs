@@ -19,7 +19,7 @@
 //! while !decoder.complete() {
 //!     let part = encoder.next_part().unwrap();
 //!     // Simulate some communication loss
-//!     if encoder.current_index() & 1 > 0 {
+//!     if encoder.current_sequence() & 1 > 0 {
 //!         decoder.receive(&part).unwrap();
 //!     }
 //! }
@@ -34,20 +34,216 @@
 //!    of a fountain encoder, which splits up a byte payload into multiple segments
 //!    and emits an unbounded stream of parts which can be recombined at the receiving
 //!    decoder side.
+//!  - The [`crate::chunked`](crate::chunked) module splits a message into single-part
+//!    URIs which can be reassembled in any order, e.g. when written to NFC tags.
+//!  - The [`crate::diagnostic`](mod@crate::diagnostic) module renders CBOR payloads
+//!    in diagnostic notation, which helps debugging payloads that can't be interpreted.
+//!  - The [`crate::estimate`](crate::estimate) module predicts the number of frames
+//!    and the duration of a transfer before starting it.
+//!  - The [`crate::transfer`](crate::transfer) module ties the encoder and decoder
+//!    together for the common case of transferring a payload via QR codes.
 
 pub mod bytewords;
+pub mod chunked;
 pub(crate) mod constants;
+pub mod diagnostic;
+pub mod estimate;
 pub mod fountain;
 pub(crate) mod sampler;
+#[cfg(any(test, feature = "simulate"))]
+pub mod simulate;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transfer;
 pub mod ur;
 pub(crate) mod xoshiro;
 
 pub use self::ur::decode;
+#[allow(deprecated)]
 pub use self::ur::encode;
+pub use self::ur::encode_ur;
+pub use self::ur::parse_many;
 pub use self::ur::Decoder;
 pub use self::ur::Encoder;
 
+/// Renders a CBOR payload in diagnostic notation with the default
+/// [`diagnostic::Limits`], see the [`diagnostic`](mod@diagnostic) module.
+///
+/// # Examples
+///
+/// ```
+/// let cbor = hex::decode("a2016474657874028202f93e00").unwrap();
+/// assert_eq!(ur::diagnostic(&cbor), "{1: \"text\", 2: [2, 1.5]}");
+/// ```
+#[must_use]
+pub fn diagnostic(cbor: &[u8]) -> String {
+    diagnostic::render(cbor, &diagnostic::Limits::default())
+}
+
+/// A stable numeric code identifying an error condition, e.g. for foreign
+/// function interfaces and logging.
+///
+/// Unlike the `Display` output of an error, codes never change across releases
+/// and are never reused. Codes are grouped by module:
+///  - `1xx`: [`bytewords::Error`]
+///  - `2xx`: the errors of the [`fountain`] module, e.g. [`fountain::DegreeError`]
+///  - `3xx`: the errors of the [`ur`](mod@ur) module, e.g. [`ur::ParseError`]
+///
+/// Errors without a dedicated type, e.g. for invalid arguments, have no code.
+///
+/// # Examples
+///
+/// ```
+/// let error = ur::bytewords::decode("able", &ur::bytewords::Style::Standard).unwrap_err();
+/// assert_eq!(error.code(), ur::ErrorCode(103));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorCode(pub u16);
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "E{:03}", self.0)
+    }
+}
+
+/// Returns four words identifying a message, which both sides of a transfer can
+/// display for the user to confirm they're handling the same message.
+///
+/// Following the `bytewords` identifier convention, these are the standard
+/// `bytewords` of the first four bytes of the SHA-256 digest of the message.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     ur::message_id_words("Ten chars!".as_bytes()),
+///     ["zest", "foxy", "work", "foxy"]
+/// );
+/// ```
+#[must_use]
+pub fn message_id_words(message: &[u8]) -> [&'static str; 4] {
+    use bitcoin_hashes::Hash;
+    let digest = bitcoin_hashes::sha256::Hash::hash(message).into_inner();
+    let mut words = [""; 4];
+    for (word, byte) in words.iter_mut().zip(digest) {
+        *word = constants::WORDS
+            .get(usize::from(byte))
+            .copied()
+            .unwrap_or_default();
+    }
+    words
+}
+
+/// The standard `bytewords` of the CRC32 checksum of a message, which are
+/// already known before a multi-part transfer completes.
+///
+/// These are provisional and differ from the [`message_id_words`] of the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChecksumWords(pub [&'static str; 4]);
+
+impl ChecksumWords {
+    pub(crate) fn new(checksum: u32) -> Self {
+        let mut words = [""; 4];
+        for (word, byte) in words.iter_mut().zip(checksum.to_be_bytes()) {
+            *word = constants::WORDS
+                .get(usize::from(byte))
+                .copied()
+                .unwrap_or_default();
+        }
+        Self(words)
+    }
+}
+
 #[must_use]
 pub(crate) fn crc32() -> crc::Crc<u32> {
     crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        use bytewords::Error;
+        let table = [
+            (Error::InvalidWord.code(), 101),
+            (Error::InvalidChecksum.code(), 102),
+            (
+                Error::Truncated {
+                    missing_chars_at_least: 1,
+                }
+                .code(),
+                103,
+            ),
+            (Error::Uncorrectable.code(), 104),
+            (Error::Ambiguous.code(), 105),
+            (Error::TooLong { max_words: 1 }.code(), 106),
+            (fountain::EmptyMessage.code(), 201),
+            (
+                fountain::MessageChecksum {
+                    expected: 0,
+                    got: 1,
+                }
+                .code(),
+                202,
+            ),
+            (
+                fountain::DegreeError {
+                    degree: 2,
+                    max_accepted_degree: 1,
+                }
+                .code(),
+                203,
+            ),
+            (fountain::FragmentConflict { index: 0 }.code(), 204),
+            (fountain::SequenceRegression { last: 2, got: 1 }.code(), 205),
+            (fountain::ConflictingPart { sequence: 1 }.code(), 206),
+            (fountain::InconsistentPart.code(), 207),
+            (fountain::InvalidPadding.code(), 208),
+            (fountain::ZeroFragmentLength.code(), 209),
+            (ur::ParseError::InvalidScheme.code(), 301),
+            (ur::ParseError::MissingType.code(), 302),
+            (ur::ParseError::InvalidType.code(), 303),
+            (ur::ParseError::InvalidIndices.code(), 304),
+            (
+                ur::TypeNotAllowed {
+                    ur_type: String::new(),
+                }
+                .code(),
+                305,
+            ),
+            (
+                ur::StrayText {
+                    text: String::new(),
+                }
+                .code(),
+                306,
+            ),
+            (
+                ur::HeaderMismatch {
+                    header: (1, 1),
+                    part: (1, 2),
+                }
+                .code(),
+                307,
+            ),
+            (
+                ur::TypeMismatch {
+                    expected: String::new(),
+                    got: String::new(),
+                }
+                .code(),
+                308,
+            ),
+        ];
+        for (code, expected) in &table {
+            assert_eq!(*code, ErrorCode(*expected));
+        }
+        let mut codes: Vec<_> = table.iter().map(|(_, code)| code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), table.len());
+        assert_eq!(ErrorCode(7).to_string(), "E007");
+    }
+}
@@ -1,4 +1,21 @@
-#[derive(Debug)]
+/// The reasons a set of weights can't be sampled from.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum WeightError {
+    /// No weights were given.
+    Empty,
+    /// The weight at the index is infinite or NaN.
+    NonFinite(usize),
+    /// The weight at the index is subnormal.
+    Subnormal(usize),
+    /// The weight at the index is negative.
+    Negative(usize),
+    /// All weights are zero.
+    AllZero,
+    /// The weights are too large or too small to be normalized.
+    Overflow,
+}
+
+#[derive(Clone, Debug)]
 pub(crate) struct Weighted {
     aliases: Vec<u32>,
     probs: Vec<f64>,
@@ -7,13 +24,28 @@ pub(crate) struct Weighted {
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_precision_loss)]
 impl Weighted {
-    pub(crate) fn new(mut weights: Vec<f64>) -> Self {
-        assert!(
-            !weights.iter().any(|&p| p < 0.0),
-            "negative probability encountered"
-        );
+    pub(crate) fn new(mut weights: Vec<f64>) -> Result<Self, WeightError> {
+        if weights.is_empty() {
+            return Err(WeightError::Empty);
+        }
+        for (index, &weight) in weights.iter().enumerate() {
+            if !weight.is_finite() {
+                return Err(WeightError::NonFinite(index));
+            }
+            if weight != 0.0 && !weight.is_normal() {
+                return Err(WeightError::Subnormal(index));
+            }
+            if weight < 0.0 {
+                return Err(WeightError::Negative(index));
+            }
+        }
         let summed = weights.iter().sum::<f64>();
-        assert!(summed > 0.0, "probabilities don't sum to a positive value");
+        if summed == 0.0 {
+            return Err(WeightError::AllZero);
+        }
+        if !(weights.len() as f64 / summed).is_finite() || !summed.is_finite() {
+            return Err(WeightError::Overflow);
+        }
         let count = weights.len();
         for w in &mut weights {
             *w *= count as f64 / summed;
@@ -48,7 +80,20 @@ impl Weighted {
             *probs.get_mut(a).unwrap() = 1.0;
         }
 
-        Self { aliases, probs }
+        Ok(Self { aliases, probs })
+    }
+
+    // The degree distribution of the fountain code, favouring low degrees.
+    pub(crate) fn degrees(length: usize) -> Self {
+        debug_assert!(length > 0, "expected a positive fragment count");
+        Self::new((1..=length.max(1)).map(|x| 1.0 / x as f64).collect())
+            .expect("degree weights are positive and finite")
+    }
+
+    // Returns the number of bytes allocated for the alias table.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.aliases.capacity() * std::mem::size_of::<u32>()
+            + self.probs.capacity() * std::mem::size_of::<f64>()
     }
 
     #[allow(clippy::cast_sign_loss)]
@@ -73,7 +118,7 @@ mod tests {
     fn test_sampler() {
         let weights = vec![1.0, 2.0, 4.0, 8.0];
         let mut xoshiro = crate::xoshiro::Xoshiro256::from("Wolf");
-        let mut sampler = Weighted::new(weights);
+        let mut sampler = Weighted::new(weights).unwrap();
 
         let expected_samples = vec![
             3, 3, 3, 3, 3, 3, 3, 0, 2, 3, 3, 3, 3, 1, 2, 2, 1, 3, 3, 2, 3, 3, 1, 1, 2, 1, 1, 3, 1,
@@ -118,21 +163,43 @@ mod tests {
         for nonce in 1..=200 {
             let mut xoshiro = crate::xoshiro::Xoshiro256::from(format!("Wolf-{}", nonce).as_str());
             assert_eq!(
-                xoshiro.choose_degree(fragments.len()),
+                xoshiro.choose_degree(&mut Weighted::degrees(fragments.len())),
                 *expected_degrees.get(nonce - 1).unwrap()
             );
         }
     }
 
     #[test]
-    #[should_panic(expected = "negative probability encountered")]
-    fn test_negative_weights() {
-        Weighted::new(vec![2.0, -1.0]);
-    }
-
-    #[test]
-    #[should_panic(expected = "probabilities don't sum to a positive value")]
-    fn test_zero_weights() {
-        Weighted::new(vec![0.0]);
+    fn test_invalid_weights() {
+        assert_eq!(Weighted::new(vec![]).unwrap_err(), WeightError::Empty);
+        assert_eq!(
+            Weighted::new(vec![1.0, f64::NAN]).unwrap_err(),
+            WeightError::NonFinite(1)
+        );
+        assert_eq!(
+            Weighted::new(vec![f64::INFINITY]).unwrap_err(),
+            WeightError::NonFinite(0)
+        );
+        assert_eq!(
+            Weighted::new(vec![1.0, 2.0, f64::MIN_POSITIVE / 2.0]).unwrap_err(),
+            WeightError::Subnormal(2)
+        );
+        assert_eq!(
+            Weighted::new(vec![2.0, -1.0]).unwrap_err(),
+            WeightError::Negative(1)
+        );
+        assert_eq!(
+            Weighted::new(vec![0.0, 0.0]).unwrap_err(),
+            WeightError::AllZero
+        );
+        assert_eq!(
+            Weighted::new(vec![f64::MAX, f64::MAX]).unwrap_err(),
+            WeightError::Overflow
+        );
+        assert_eq!(
+            Weighted::new([vec![f64::MIN_POSITIVE], vec![0.0; 9]].concat()).unwrap_err(),
+            WeightError::Overflow
+        );
+        Weighted::new(vec![0.0, 1.0]).unwrap();
     }
 }
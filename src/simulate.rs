@@ -0,0 +1,377 @@
+//! Simulate transfers over lossy channels, reproducibly.
+//!
+//! This module is only available with the `simulate` feature. A [`Simulation`]
+//! runs the [`crate::Encoder`] and [`crate::Decoder`] of this crate over a
+//! [`Channel`] which loses parts according to a [`Loss`] model. Both the message
+//! and the channel are derived from a seed, so a simulation with the same
+//! parameters always has the same [`Outcome`], which can be written as CSV.
+//! ```
+//! use ur::simulate::{Loss, Simulation, CSV_HEADER};
+//! let outcome = Simulation::new(1000, 100, Loss::Iid(0.25), 7).run().unwrap();
+//! assert_eq!(outcome.fragment_count, 10);
+//! assert!(outcome.parts_to_complete >= 10);
+//! assert!(outcome.parts_received <= outcome.parts_sent);
+//! assert_eq!(outcome, Simulation::new(1000, 100, Loss::Iid(0.25), 7).run().unwrap());
+//! println!("{}\n{}", CSV_HEADER, outcome.csv_row());
+//! ```
+
+/// The header of the rows written by [`Outcome::csv_row`].
+pub const CSV_HEADER: &str = "message_length,max_fragment_length,loss_model,loss,burst_length,\
+                              seed,fps,fragment_count,parts_sent,parts_received,\
+                              parts_to_complete,duration_ms";
+
+/// How a [`Channel`] loses parts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loss {
+    /// Each part is lost independently with the given probability.
+    Iid(f64),
+    /// Parts are lost in bursts, as when the camera of the receiver is moved
+    /// away for a moment.
+    ///
+    /// The channel alternates between passing and losing parts as a Gilbert
+    /// model: the bursts have a geometrically distributed length, and start such
+    /// that the given fraction of all parts is lost.
+    Burst {
+        /// The fraction of parts lost in the long run.
+        loss: f64,
+        /// The mean number of parts lost in a row.
+        burst_length: f64,
+    },
+}
+
+/// A channel losing parts according to a [`Loss`] model.
+pub struct Channel {
+    rng: crate::xoshiro::Xoshiro256,
+    // the probabilities to start and to end a burst
+    start: f64,
+    end: f64,
+    bursting: bool,
+}
+
+impl Channel {
+    /// Creates a channel, seeding its randomness with the given seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::simulate::{Channel, Loss};
+    /// let mut channel = Channel::new(Loss::Iid(0.5), 1).unwrap();
+    /// let passed = (0..1000).filter(|_| channel.pass()).count();
+    /// assert!((400..600).contains(&passed));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the loss is not in `[0, 1)`, a burst length is less than one, or the
+    /// loss can't be reached with bursts of that length, i.e. exceeds
+    /// `burst_length / (burst_length + 1)`, an error will be returned.
+    pub fn new(loss: Loss, seed: u64) -> anyhow::Result<Self> {
+        let (start, end) = match loss {
+            Loss::Iid(loss) => {
+                anyhow::ensure!((0.0..1.0).contains(&loss), "expected loss in [0, 1)");
+                (loss, 1.0 - loss)
+            }
+            Loss::Burst { loss, burst_length } => {
+                anyhow::ensure!((0.0..1.0).contains(&loss), "expected loss in [0, 1)");
+                anyhow::ensure!(burst_length >= 1.0, "expected burst length of at least 1");
+                let end = 1.0 / burst_length;
+                let start = end * loss / (1.0 - loss);
+                anyhow::ensure!(
+                    start <= 1.0,
+                    "expected loss of at most {} for bursts of length {}",
+                    burst_length / (burst_length + 1.0),
+                    burst_length
+                );
+                (start, end)
+            }
+        };
+        let mut rng = crate::xoshiro::Xoshiro256::from(&seed.to_be_bytes()[..]);
+        // start in the long-run state to avoid a bias towards short transfers
+        let bursting = rng.next_double() < start / (start + end);
+        Ok(Self {
+            rng,
+            start,
+            end,
+            bursting,
+        })
+    }
+
+    /// Returns whether the next part passes the channel.
+    ///
+    /// # Examples
+    ///
+    /// See [`Channel::new`] for an example.
+    pub fn pass(&mut self) -> bool {
+        let pass = !self.bursting;
+        let change = if self.bursting { self.end } else { self.start };
+        if self.rng.next_double() < change {
+            self.bursting = !self.bursting;
+        }
+        pass
+    }
+}
+
+/// The parameters of a simulated transfer.
+///
+/// # Examples
+///
+/// See the [`crate::simulate`] module documentation for an example.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Simulation {
+    message_length: usize,
+    max_fragment_length: usize,
+    loss: Loss,
+    seed: u64,
+    fps: f32,
+}
+
+impl Simulation {
+    /// Creates a simulation of a transfer of a message of the given length,
+    /// displaying 8 frames per second.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::simulate`] module documentation for an example.
+    #[must_use]
+    pub fn new(message_length: usize, max_fragment_length: usize, loss: Loss, seed: u64) -> Self {
+        Self {
+            message_length,
+            max_fragment_length,
+            loss,
+            seed,
+            fps: 8.0,
+        }
+    }
+
+    /// Sets the number of frames displayed per second, which determines the
+    /// [`Outcome::duration`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::simulate::{Loss, Simulation};
+    /// let simulation = Simulation::new(100, 10, Loss::Iid(0.0), 1).with_fps(20.0);
+    /// let outcome = simulation.run().unwrap();
+    /// assert_eq!(outcome.parts_sent, 10);
+    /// assert_eq!(outcome.duration, std::time::Duration::from_millis(500));
+    /// ```
+    #[must_use]
+    pub fn with_fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    /// Runs the simulation: a message derived from the seed is encoded into
+    /// parts, which are passed over a [`Channel`] until the decoder completes.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::simulate`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// If an empty message, a zero maximum fragment length, a non-positive frame
+    /// rate or an invalid loss model is passed, an error will be returned.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn run(&self) -> anyhow::Result<Outcome> {
+        anyhow::ensure!(
+            self.fps > 0.0 && self.fps.is_finite(),
+            "expected positive frame rate"
+        );
+        let mut channel = Channel::new(self.loss, self.seed)?;
+        let mut rng = crate::xoshiro::Xoshiro256::from(format!("message-{}", self.seed).as_str());
+        let message: Vec<u8> = (0..self.message_length)
+            .map(|_| rng.next_int(0, 255) as u8)
+            .collect();
+        let mut encoder = crate::Encoder::new(&message, self.max_fragment_length, "bytes")?;
+        let mut decoder = crate::Decoder::default();
+        let mut parts_received = 0;
+        while !decoder.complete() {
+            let part = encoder.next_part()?;
+            if channel.pass() {
+                decoder.receive(&part)?;
+                parts_received += 1;
+            }
+        }
+        anyhow::ensure!(
+            decoder.message()?.as_deref() == Some(message.as_slice()),
+            "decoded a wrong message"
+        );
+        let parts_sent = encoder.current_sequence();
+        Ok(Outcome {
+            simulation: *self,
+            fragment_count: encoder.fragment_count(),
+            parts_sent,
+            parts_received,
+            parts_to_complete: parts_received - decoder.statistics().duplicate_parts,
+            duration: std::time::Duration::from_secs_f64(parts_sent as f64 / f64::from(self.fps)),
+        })
+    }
+}
+
+/// The outcome of a [`Simulation`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outcome {
+    /// The simulation which had this outcome.
+    pub simulation: Simulation,
+    /// The number of fragments the message was split up into.
+    pub fragment_count: usize,
+    /// The number of parts sent until the decoder completed.
+    pub parts_sent: usize,
+    /// The number of parts which passed the channel.
+    pub parts_received: usize,
+    /// The number of received parts which contributed to completing the decoder,
+    /// i.e. excluding parts whose fragments had all been received before.
+    pub parts_to_complete: usize,
+    /// The time it took to display the sent parts.
+    pub duration: std::time::Duration,
+}
+
+impl Outcome {
+    /// Formats the simulation and its outcome as a CSV row, see [`CSV_HEADER`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::simulate::{Loss, Simulation};
+    /// let loss = Loss::Burst {
+    ///     loss: 0.2,
+    ///     burst_length: 3.0,
+    /// };
+    /// let outcome = Simulation::new(100, 10, loss, 1).run().unwrap();
+    /// assert!(outcome.csv_row().starts_with("100,10,burst,0.2,3,1,8,10,"));
+    /// ```
+    #[must_use]
+    pub fn csv_row(&self) -> String {
+        let simulation = &self.simulation;
+        let (model, loss, burst_length) = match simulation.loss {
+            Loss::Iid(loss) => ("iid", loss, String::new()),
+            Loss::Burst { loss, burst_length } => ("burst", loss, burst_length.to_string()),
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            simulation.message_length,
+            simulation.max_fragment_length,
+            model,
+            loss,
+            burst_length,
+            simulation.seed,
+            simulation.fps,
+            self.fragment_count,
+            self.parts_sent,
+            self.parts_received,
+            self.parts_to_complete,
+            self.duration.as_millis()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The totals over a hundred seeds of transfers of 1000 bytes in fragments of
+    // at most 100 bytes. A change of these is a change of the efficiency of the
+    // fountain code and should be justified in review.
+    fn totals(loss: Loss) -> (usize, usize, usize) {
+        (0..100).fold((0, 0, 0), |(sent, received, to_complete), seed| {
+            let outcome = Simulation::new(1000, 100, loss, seed).run().unwrap();
+            (
+                sent + outcome.parts_sent,
+                received + outcome.parts_received,
+                to_complete + outcome.parts_to_complete,
+            )
+        })
+    }
+
+    #[test]
+    fn test_regression_iid() {
+        assert_eq!(totals(Loss::Iid(0.0)), (1000, 1000, 1000));
+        assert_eq!(totals(Loss::Iid(0.25)), (1723, 1262, 1037));
+    }
+
+    #[test]
+    fn test_regression_burst() {
+        let loss = Loss::Burst {
+            loss: 0.25,
+            burst_length: 4.0,
+        };
+        assert_eq!(totals(loss), (1711, 1220, 1040));
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_channel_loss() {
+        for loss in [
+            Loss::Iid(0.3),
+            Loss::Burst {
+                loss: 0.3,
+                burst_length: 1.0,
+            },
+            Loss::Burst {
+                loss: 0.3,
+                burst_length: 10.0,
+            },
+        ] {
+            let mut channel = Channel::new(loss, 42).unwrap();
+            let passes: Vec<bool> = (0..100_000).map(|_| channel.pass()).collect();
+            let lost = passes.iter().filter(|&&pass| !pass).count() as f64 / 100_000.0;
+            assert!((lost - 0.3).abs() < 0.01, "{loss:?} lost {lost}");
+            let bursts = passes.windows(2).filter(|w| w == &[true, false]).count() as f64;
+            let burst_length = match loss {
+                Loss::Iid(_) => 1.0 / 0.7,
+                Loss::Burst { burst_length, .. } => burst_length,
+            };
+            let mean = lost * 100_000.0 / bursts;
+            assert!(
+                (mean - burst_length).abs() < 0.05 * burst_length,
+                "{loss:?} has mean burst length {mean}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        for (loss, error) in [
+            (Loss::Iid(1.0), "expected loss in [0, 1)"),
+            (Loss::Iid(-0.1), "expected loss in [0, 1)"),
+            (Loss::Iid(f64::NAN), "expected loss in [0, 1)"),
+            (
+                Loss::Burst {
+                    loss: 0.5,
+                    burst_length: 0.5,
+                },
+                "expected burst length of at least 1",
+            ),
+            (
+                Loss::Burst {
+                    loss: 0.6,
+                    burst_length: 1.0,
+                },
+                "expected loss of at most 0.5 for bursts of length 1",
+            ),
+        ] {
+            assert_eq!(Channel::new(loss, 0).err().unwrap().to_string(), error);
+        }
+        let run = |simulation: Simulation| simulation.run().unwrap_err().to_string();
+        assert_eq!(
+            run(Simulation::new(0, 10, Loss::Iid(0.0), 0)),
+            "expected non-empty message"
+        );
+        assert_eq!(
+            run(Simulation::new(10, 10, Loss::Iid(0.0), 0).with_fps(0.0)),
+            "expected positive frame rate"
+        );
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let outcome = Simulation::new(1000, 100, Loss::Iid(0.0), 3).run().unwrap();
+        assert_eq!(outcome.csv_row(), "1000,100,iid,0,,3,8,10,10,10,10,1250");
+        assert_eq!(
+            CSV_HEADER.split(',').count(),
+            outcome.csv_row().split(',').count()
+        );
+    }
+}
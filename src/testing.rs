@@ -0,0 +1,291 @@
+//! Mutate multi-part transmissions to test decoders against damaged streams.
+//!
+//! This module is only available with the `testing` feature. A [`Stream`] holds
+//! the properly encoded parts of a message, to which [`Mutation`]s such as lost,
+//! repeated, reordered, corrupted or foreign parts are applied. Mutations can be
+//! derived from arbitrary bytes, which makes the module suitable to drive fuzzing.
+//! ```
+//! use ur::testing::{Mutation, Stream};
+//! let mut stream = Stream::new("Ten chars!".as_bytes(), 4, 2).unwrap();
+//! assert_eq!(stream.parts().len(), 5);
+//! stream.apply(Mutation::Drop(0));
+//! stream.apply(Mutation::Corrupt { part: 0, position: 7 });
+//! stream.check();
+//! ```
+
+/// A change applied to the parts of a [`Stream`].
+///
+/// Part indexes and positions are taken modulo the respective lengths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Removes the part at the index.
+    Drop(usize),
+    /// Repeats the part at the index right after it.
+    Duplicate(usize),
+    /// Swaps the parts at the two indexes.
+    Swap(usize, usize),
+    /// Changes one letter of the `bytewords` payload of a part.
+    Corrupt {
+        /// The index of the part.
+        part: usize,
+        /// The position of the letter within the payload.
+        position: usize,
+    },
+    /// Inserts a part of a different message at the index.
+    Splice {
+        /// The index of the part of the different message.
+        part: usize,
+        /// The index the part is inserted at.
+        position: usize,
+    },
+}
+
+impl Mutation {
+    /// Derives a sequence of mutations from arbitrary bytes,
+    /// consuming five bytes per mutation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::testing::Mutation;
+    /// assert_eq!(
+    ///     Mutation::from_bytes(&[0, 0, 1, 0, 0, 2, 0, 3, 1, 0, 9]),
+    ///     vec![Mutation::Drop(1), Mutation::Swap(3, 256)]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_bytes(data: &[u8]) -> Vec<Self> {
+        data.chunks_exact(5)
+            .map(|chunk| {
+                let number = |offset: usize| {
+                    usize::from(u16::from_be_bytes([
+                        chunk.get(offset).copied().unwrap_or_default(),
+                        chunk.get(offset + 1).copied().unwrap_or_default(),
+                    ]))
+                };
+                let (a, b) = (number(1), number(3));
+                match chunk.first().copied().unwrap_or_default() % 5 {
+                    0 => Self::Drop(a),
+                    1 => Self::Duplicate(a),
+                    2 => Self::Swap(a, b),
+                    3 => Self::Corrupt {
+                        part: a,
+                        position: b,
+                    },
+                    _ => Self::Splice {
+                        part: a,
+                        position: b,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// The multi-part transmission of a message, subject to [`Mutation`]s.
+#[derive(Clone, Debug)]
+pub struct Stream {
+    message: Vec<u8>,
+    pure: Vec<String>,
+    parts: Vec<String>,
+    other_message: Vec<u8>,
+    other: Vec<String>,
+}
+
+impl Stream {
+    /// Encodes a message into the parts covering all fragments once,
+    /// followed by the given number of further parts.
+    ///
+    /// The parts spliced in by [`Mutation::Splice`] are those of the message
+    /// extended by one byte, encoded with the same maximum fragment length.
+    ///
+    /// # Errors
+    ///
+    /// If an empty message or a zero maximum fragment length is passed, an error
+    /// will be returned.
+    pub fn new(
+        message: &[u8],
+        max_fragment_length: usize,
+        extra_parts: usize,
+    ) -> anyhow::Result<Self> {
+        let encode = |message: &[u8]| -> anyhow::Result<(Vec<String>, usize)> {
+            let mut encoder = crate::Encoder::new(message, max_fragment_length, "bytes")?;
+            let count = encoder.fragment_count() + extra_parts;
+            let parts = (0..count)
+                .map(|_| encoder.next_part())
+                .collect::<anyhow::Result<_>>()?;
+            Ok((parts, encoder.fragment_count()))
+        };
+        let (parts, fragment_count) = encode(message)?;
+        let other_message = [message, &[0]].concat();
+        let (other, _) = encode(&other_message)?;
+        Ok(Self {
+            message: message.to_vec(),
+            pure: parts.iter().take(fragment_count).cloned().collect(),
+            parts,
+            other_message,
+            other,
+        })
+    }
+
+    /// Returns the current parts of the stream.
+    #[must_use]
+    pub fn parts(&self) -> &[String] {
+        &self.parts
+    }
+
+    /// Applies a mutation to the parts of the stream.
+    pub fn apply(&mut self, mutation: Mutation) {
+        let len = self.parts.len();
+        match mutation {
+            _ if len == 0 => {
+                if let Mutation::Splice { part, .. } = mutation {
+                    self.splice(part, 0);
+                }
+            }
+            Mutation::Drop(index) => {
+                self.parts.remove(index % len);
+            }
+            Mutation::Duplicate(index) => {
+                let part = self.parts.get(index % len).cloned().unwrap_or_default();
+                self.parts.insert(index % len, part);
+            }
+            Mutation::Swap(a, b) => self.parts.swap(a % len, b % len),
+            Mutation::Corrupt { part, position } => {
+                if let Some(part) = self.parts.get_mut(part % len) {
+                    *part = corrupt(part, position);
+                }
+            }
+            Mutation::Splice { part, position } => self.splice(part, position % (len + 1)),
+        }
+    }
+
+    fn splice(&mut self, part: usize, position: usize) {
+        let other = self
+            .other
+            .get(part % self.other.len())
+            .cloned()
+            .unwrap_or_default();
+        self.parts.insert(position, other);
+    }
+
+    /// Receives all parts into a [`crate::Decoder`] and asserts that the decoder
+    /// neither completes with a wrong message, nor fails to complete although
+    /// all fragments of the message were received uncorrupted.
+    ///
+    /// The decoder sticks to the message of the first part it accepts, which
+    /// may be the spliced-in one.
+    ///
+    /// # Panics
+    ///
+    /// If one of the invariants is violated.
+    pub fn check(&self) {
+        let mut decoder = crate::Decoder::default();
+        let mut spliced = None;
+        for part in &self.parts {
+            if decoder.receive(part).is_ok() && spliced.is_none() {
+                spliced = Some(self.other.contains(part));
+            }
+        }
+        let message = match spliced {
+            Some(true) => &self.other_message,
+            _ => &self.message,
+        };
+        if decoder.complete() {
+            assert_eq!(
+                decoder.message().unwrap().as_deref(),
+                Some(message.as_slice()),
+                "decoder completed with a wrong message"
+            );
+        }
+        if spliced == Some(false) && self.pure.iter().all(|part| self.parts.contains(part)) {
+            assert!(
+                decoder.complete(),
+                "decoder did not complete although all fragments were received"
+            );
+        }
+    }
+}
+
+// Changes the letter at the position within the payload to the next one in the alphabet.
+fn corrupt(part: &str, position: usize) -> String {
+    let payload_start = part.rfind('/').map_or(0, |index| index + 1);
+    let payload_length = part.len() - payload_start;
+    if payload_length == 0 {
+        return part.to_string();
+    }
+    let index = payload_start + position % payload_length;
+    part.char_indices()
+        .map(|(i, c)| match c {
+            'a'..='y' if i == index => char::from(c as u8 + 1),
+            'z' if i == index => 'a',
+            _ => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrupt() {
+        assert_eq!(corrupt("ur:bytes/1-2/abz", 0), "ur:bytes/1-2/bbz");
+        assert_eq!(corrupt("ur:bytes/1-2/abz", 5), "ur:bytes/1-2/aba");
+        assert_eq!(corrupt("ur:bytes/1-2/", 5), "ur:bytes/1-2/");
+    }
+
+    #[test]
+    fn test_mutated_streams() {
+        for run in 0..200 {
+            let seed = format!("stream-{run}");
+            let mut rng = crate::xoshiro::Xoshiro256::from(seed.as_str());
+            #[allow(clippy::cast_possible_truncation)]
+            let message_length = rng.next_int(1, 200) as usize;
+            #[allow(clippy::cast_possible_truncation)]
+            let max_fragment_length = rng.next_int(1, 30) as usize;
+            #[allow(clippy::cast_possible_truncation)]
+            let extra_parts = rng.next_int(0, 20) as usize;
+            #[allow(clippy::cast_possible_truncation)]
+            let mutation_count = rng.next_int(0, 10) as usize;
+            let message = rng.next_bytes(message_length);
+            let mut stream = Stream::new(&message, max_fragment_length, extra_parts).unwrap();
+            for mutation in Mutation::from_bytes(&rng.next_bytes(5 * mutation_count)) {
+                stream.apply(mutation);
+            }
+            stream.check();
+        }
+    }
+
+    #[test]
+    fn test_all_fragments_complete() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 100);
+        let mut stream = Stream::new(&message, 10, 5).unwrap();
+        for mutation in [
+            Mutation::Duplicate(3),
+            Mutation::Swap(0, 9),
+            Mutation::Drop(14),
+            Mutation::Corrupt {
+                part: 12,
+                position: 3,
+            },
+            Mutation::Splice {
+                part: 4,
+                position: 5,
+            },
+        ] {
+            stream.apply(mutation);
+        }
+        stream.check();
+
+        // a spliced part received first determines the decoded message
+        let mut stream = Stream::new(&message, 10, 0).unwrap();
+        for part in 0..11 {
+            stream.apply(Mutation::Splice {
+                part,
+                position: part,
+            });
+        }
+        stream.check();
+    }
+}
@@ -0,0 +1,374 @@
+//! Transfer a payload as a sequence of URIs, e.g. via animated QR codes.
+//!
+//! The `transfer` module ties together the [`crate::ur`] encoder and decoder.
+//! [`send`] emits the parts for a payload, choosing the fragment length such that
+//! every part fits into the capacity given by a [`Profile`], and a [`Receiver`]
+//! collects scanned parts, whether the payload fits into a single one or not.
+//! ```
+//! use ur::transfer::{send, Profile, Receiver};
+//! let psbt = "psbt".repeat(100);
+//! let profile = Profile {
+//!     max_part_length: 200,
+//!     ..Profile::default()
+//! };
+//! let mut receiver = Receiver::new(Some("crypto-psbt"));
+//! for part in send(psbt.as_bytes(), "crypto-psbt", &profile).unwrap() {
+//!     assert!(part.len() <= 200);
+//!     if receiver.receive(&part).unwrap().complete() {
+//!         break;
+//!     }
+//! }
+//! let (ur_type, message) = receiver.finish().unwrap();
+//! assert_eq!(ur_type, "crypto-psbt");
+//! assert_eq!(message, psbt.as_bytes());
+//! ```
+
+use anyhow::Context;
+
+/// The characters a multi-part URI adds to twice its fragment length, at most.
+///
+/// These are the `ur:` scheme, two separators, the `<seq>-<seq-count>` header
+/// with 32-bit numbers, the CBOR array of four 32-bit integers and the fragment
+/// byte string header, and the `bytewords` checksum.
+const PART_OVERHEAD: usize = 3 + 2 + 21 + 2 * (1 + 4 * 5 + 3) + 8;
+
+/// Choices for sending a payload with [`send`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile {
+    /// The maximum number of characters of an emitted part,
+    /// e.g. the alphanumeric capacity of the QR code version in use.
+    pub max_part_length: usize,
+    /// The number of emitted parts per message fragment, or `None`
+    /// for an unbounded stream of parts.
+    pub redundancy: Option<usize>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            max_part_length: 250,
+            redundancy: None,
+        }
+    }
+}
+
+/// Returns the largest maximum fragment length such that the parts emitted by
+/// a [`crate::ur::Encoder`] of the given type don't exceed the given length,
+/// or `None` if no fragment fits.
+///
+/// # Examples
+///
+/// ```
+/// let max_fragment_length = ur::transfer::max_fragment_length("bytes", 200).unwrap();
+/// let mut encoder = ur::Encoder::new(&[0; 1000], max_fragment_length, "bytes").unwrap();
+/// assert!(encoder.next_part().unwrap().len() <= 200);
+/// assert_eq!(ur::transfer::max_fragment_length("bytes", 80), None);
+/// ```
+#[must_use]
+pub const fn max_fragment_length(ur_type: &str, max_part_length: usize) -> Option<usize> {
+    let overhead = PART_OVERHEAD + ur_type.len();
+    if max_part_length < overhead + 2 {
+        return None;
+    }
+    Some((max_part_length - overhead) / 2)
+}
+
+/// Returns the maximum length of the parts emitted by a [`crate::ur::Encoder`]
+/// of the given type and maximum fragment length, the inverse of [`max_fragment_length`].
+///
+/// # Examples
+///
+/// ```
+/// let max_part_length = ur::transfer::max_part_length("bytes", 60);
+/// let mut encoder = ur::Encoder::new(&[0; 1000], 60, "bytes").unwrap();
+/// assert!(encoder.next_part().unwrap().len() <= max_part_length);
+/// assert_eq!(ur::transfer::max_fragment_length("bytes", max_part_length), Some(60));
+/// ```
+#[must_use]
+pub const fn max_part_length(ur_type: &str, max_fragment_length: usize) -> usize {
+    PART_OVERHEAD + ur_type.len() + 2 * max_fragment_length
+}
+
+/// Returns the parts to transfer a payload of the given type.
+///
+/// If the payload fits into a single-part URI, this is the only emitted part.
+/// Otherwise, the multi-part URIs of a [`crate::ur::Encoder`] are emitted.
+///
+/// # Examples
+///
+/// See the [`crate::transfer`] module documentation for an example.
+///
+/// # Errors
+///
+/// If an empty payload or an invalid type is passed, or the maximum part length
+/// of the profile is too small to fit any fragment, an error will be returned.
+pub fn send(
+    message: &[u8],
+    ur_type: &str,
+    profile: &Profile,
+) -> anyhow::Result<impl Iterator<Item = String>> {
+    anyhow::ensure!(!message.is_empty(), "expected non-empty message");
+    let single = crate::ur::encode_ur(ur_type, message)?;
+    let (mut single, mut encoder, limit) = if single.len() <= profile.max_part_length {
+        (Some(single), None, 1)
+    } else {
+        let max_fragment_length = max_fragment_length(ur_type, profile.max_part_length)
+            .context("maximum part length too small")?;
+        let encoder = crate::ur::Encoder::new(message, max_fragment_length, ur_type)?;
+        let limit = profile.redundancy.map_or(usize::MAX, |redundancy| {
+            redundancy.saturating_mul(encoder.fragment_count())
+        });
+        (None, Some(encoder), limit)
+    };
+    Ok(std::iter::from_fn(move || match &mut encoder {
+        Some(encoder) => encoder.next_part().ok(),
+        None => single.take(),
+    })
+    .take(limit))
+}
+
+/// The progress of a [`Receiver`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress {
+    /// The number of message fragments which have been decoded.
+    pub decoded_fragments: usize,
+    /// The number of fragments the message has been split up into,
+    /// zero if no part has been received yet.
+    pub fragment_count: usize,
+}
+
+impl Progress {
+    /// Returns whether the message has been received completely.
+    #[must_use]
+    pub fn complete(&self) -> bool {
+        self.fragment_count > 0 && self.decoded_fragments == self.fragment_count
+    }
+}
+
+/// A receiver of the parts emitted by [`send`].
+///
+/// # Examples
+///
+/// See the [`crate::transfer`] module documentation for an example.
+#[derive(Default)]
+pub struct Receiver {
+    expected: Option<String>,
+    ur_type: Option<String>,
+    decoder: crate::ur::Decoder,
+    single: Option<Vec<u8>>,
+}
+
+impl Receiver {
+    /// Creates a new [`Receiver`], optionally only accepting parts of the given type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut receiver = ur::transfer::Receiver::new(Some("crypto-psbt"));
+    /// assert_eq!(
+    ///     receiver
+    ///         .receive("ur:bytes/iehsjyhspmwfwfia")
+    ///         .unwrap_err()
+    ///         .to_string(),
+    ///     "expected ur type crypto-psbt, got bytes"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new(expected: Option<&str>) -> Self {
+        Self {
+            expected: expected.map(ToString::to_string),
+            ..Self::default()
+        }
+    }
+
+    /// Receives a single- or multi-part URI and returns the progress so far.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::transfer`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// If the URI has an unexpected type, a [`crate::ur::TypeMismatch`] error will
+    /// be returned. If the URI is malformed or is inconsistent with previously
+    /// received parts, an error will be returned.
+    pub fn receive(&mut self, value: &str) -> anyhow::Result<Progress> {
+        let ur = crate::ur::UR::parse(value)?;
+        let ur_type = ur.ur_type();
+        if let Some(expected) = self.expected.as_ref().or(self.ur_type.as_ref()) {
+            if expected != ur_type {
+                return Err(crate::ur::TypeMismatch {
+                    expected: expected.clone(),
+                    got: ur_type.to_string(),
+                }
+                .into());
+            }
+        }
+        if self.progress().complete() {
+            return Ok(self.progress());
+        }
+        match ur.kind() {
+            crate::ur::Kind::SinglePart => {
+                anyhow::ensure!(
+                    self.ur_type.is_none(),
+                    "single-part ur received during multi-part transfer"
+                );
+                self.single = Some(ur.decode_payload()?);
+            }
+            crate::ur::Kind::MultiPart => self.decoder.receive(value)?,
+        }
+        self.ur_type = Some(ur_type.to_string());
+        Ok(self.progress())
+    }
+
+    /// Returns the progress so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut receiver = ur::transfer::Receiver::default();
+    /// assert!(!receiver.progress().complete());
+    /// receiver.receive("ur:bytes/iehsjyhspmwfwfia").unwrap();
+    /// assert!(receiver.progress().complete());
+    /// ```
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        let (decoded_fragments, fragment_count) = if self.single.is_some() {
+            (1, 1)
+        } else {
+            self.decoder.progress()
+        };
+        Progress {
+            decoded_fragments,
+            fragment_count,
+        }
+    }
+
+    /// Returns the type and the payload of the received message.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::transfer`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// If the message hasn't been received completely, or an inconsistent
+    /// internal state is detected, an error will be returned.
+    pub fn finish(self) -> anyhow::Result<(String, Vec<u8>)> {
+        let message = match self.single {
+            Some(message) => message,
+            None => self.decoder.message()?.context("transfer incomplete")?,
+        };
+        Ok((self.ur_type.context("transfer incomplete")?, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_crypto_psbt() {
+        // a CBOR byte string wrapping a PSBT, as carried by a crypto-psbt
+        let psbt = crate::xoshiro::test_utils::make_message("psbt", 2000);
+        let message = [&[0x59, 0x07, 0xd0][..], &psbt].concat();
+        for max_part_length in [100, 250, 1000] {
+            let profile = Profile {
+                max_part_length,
+                redundancy: None,
+            };
+            let mut receiver = Receiver::new(Some("crypto-psbt"));
+            let mut skip = false;
+            for part in send(&message, "crypto-psbt", &profile).unwrap() {
+                assert!(part.len() <= max_part_length);
+                // Simulate some communication loss
+                if !skip && receiver.receive(&part).unwrap().complete() {
+                    break;
+                }
+                skip = !skip;
+            }
+            let progress = receiver.progress();
+            assert!(progress.complete());
+            assert_eq!(progress.decoded_fragments, progress.fragment_count);
+            assert_eq!(
+                receiver.finish().unwrap(),
+                ("crypto-psbt".to_string(), message.clone())
+            );
+        }
+    }
+
+    // evaluated at compile time
+    const _: () = assert!(max_part_length("bytes", 60) == 207);
+    const _: () = assert!(matches!(max_fragment_length("bytes", 207), Some(60)));
+    const _: () = assert!(max_fragment_length("bytes", 88).is_none());
+
+    #[test]
+    fn test_send() {
+        let profile = Profile::default();
+        let parts: Vec<String> = send(b"data", "bytes", &profile).unwrap().collect();
+        assert_eq!(parts, vec!["ur:bytes/iehsjyhspmwfwfia"]);
+
+        let profile = Profile {
+            max_part_length: 100,
+            redundancy: Some(2),
+        };
+        let message = [0; 100];
+        let max_fragment_length = max_fragment_length("bytes", 100).unwrap();
+        let fragment_count = crate::Encoder::new(&message, max_fragment_length, "bytes")
+            .unwrap()
+            .fragment_count();
+        assert_eq!(
+            send(&message, "bytes", &profile).unwrap().count(),
+            2 * fragment_count
+        );
+
+        let profile = Profile {
+            max_part_length: 80,
+            redundancy: None,
+        };
+        assert_eq!(
+            send(&message, "bytes", &profile).err().unwrap().to_string(),
+            "maximum part length too small"
+        );
+        assert_eq!(
+            send(&[], "bytes", &Profile::default())
+                .err()
+                .unwrap()
+                .to_string(),
+            "expected non-empty message"
+        );
+    }
+
+    #[test]
+    fn test_receiver() {
+        let mut receiver = Receiver::default();
+        assert_eq!(receiver.progress(), Progress::default());
+        let mut encoder = crate::Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        let progress = receiver.receive(&encoder.next_part().unwrap()).unwrap();
+        assert_eq!(progress.decoded_fragments, 1);
+        assert_eq!(progress.fragment_count, 10);
+        assert_eq!(
+            receiver
+                .receive("ur:bytes/iehsjyhspmwfwfia")
+                .unwrap_err()
+                .to_string(),
+            "single-part ur received during multi-part transfer"
+        );
+        assert_eq!(
+            receiver
+                .receive("ur:other/iehsjyhspmwfwfia")
+                .unwrap_err()
+                .to_string(),
+            "expected ur type bytes, got other"
+        );
+        assert_eq!(
+            Receiver::default().finish().unwrap_err().to_string(),
+            "transfer incomplete"
+        );
+        assert_eq!(
+            receiver.finish().unwrap_err().to_string(),
+            "transfer incomplete"
+        );
+    }
+}
@@ -19,7 +19,7 @@
 //! while !decoder.complete() {
 //!     let part = encoder.next_part().unwrap();
 //!     // Simulate some communication loss
-//!     if encoder.current_index() & 1 > 0 {
+//!     if encoder.current_sequence() & 1 > 0 {
 //!         decoder.receive(&part).unwrap();
 //!     }
 //! }
@@ -33,18 +33,57 @@ use anyhow::Context;
 /// # Examples
 ///
 /// ```
+/// # #![allow(deprecated)]
 /// assert_eq!(
 ///     ur::ur::encode("data".as_bytes(), "bytes"),
 ///     "ur:bytes/iehsjyhspmwfwfia"
 /// );
 /// ```
+#[deprecated(
+    note = "neither the type nor the payload is validated, use `encode_ur(ur_type, data)?` instead"
+)]
 pub fn encode<T: Into<String>>(data: &[u8], ur_type: T) -> String {
     let body = crate::bytewords::encode(data, &crate::bytewords::Style::Minimal);
-    encode_ur(&[ur_type.into(), body])
+    join(&[ur_type.into(), body])
+}
+
+/// Encodes a CBOR payload of the given type into a single URI.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     ur::encode_ur("bytes", "data".as_bytes()).unwrap(),
+///     "ur:bytes/iehsjyhspmwfwfia"
+/// );
+/// assert_eq!(
+///     ur::encode_ur("Bytes", "data".as_bytes())
+///         .unwrap_err()
+///         .to_string(),
+///     "Type contains invalid characters"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// If the type contains characters other than lowercase ASCII letters, digits
+/// and hyphens, a [`ParseError::InvalidType`] error will be returned. If the type
+/// or the payload is empty, an error will be returned.
+pub fn encode_ur(ur_type: &str, cbor: &[u8]) -> anyhow::Result<String> {
+    anyhow::ensure!(!ur_type.is_empty(), "expected non-empty type");
+    if !ur_type
+        .trim_start_matches(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        .is_empty()
+    {
+        return Err(ParseError::InvalidType.into());
+    }
+    anyhow::ensure!(!cbor.is_empty(), "expected non-empty payload");
+    let body = crate::bytewords::encode(cbor, &crate::bytewords::Style::Minimal);
+    Ok(join(&[ur_type.to_string(), body]))
 }
 
 #[must_use]
-fn encode_ur(items: &[String]) -> String {
+pub(crate) fn join(items: &[String]) -> String {
     format!("{}:{}", "ur", items.join("/"))
 }
 
@@ -53,6 +92,7 @@ fn encode_ur(items: &[String]) -> String {
 /// # Examples
 ///
 /// See the [`crate::ur`] module documentation for an example.
+#[derive(Clone)]
 pub struct Encoder {
     fountain: crate::fountain::Encoder,
     ur_type: String,
@@ -82,6 +122,66 @@ impl Encoder {
         })
     }
 
+    /// Creates a new [`Encoder`] using the given fragments as message segments,
+    /// see [`crate::fountain::Encoder::from_fragments`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let fragments: [&[u8]; 2] = [b"da", b"ta"];
+    /// let mut encoder = ur::Encoder::from_fragments(&fragments, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// assert_eq!(decoder.message().unwrap().as_deref(), Some("data".as_bytes()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If no fragments, an empty first fragment, or fragments with inconsistent
+    /// lengths are passed, an error will be returned.
+    pub fn from_fragments<T: Into<String>>(
+        fragments: &[&[u8]],
+        ur_type: T,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            fountain: crate::fountain::Encoder::from_fragments(fragments)?,
+            ur_type: ur_type.into(),
+        })
+    }
+
+    /// Creates a new [`Encoder`] for a message of the given length read from
+    /// a reader, see [`crate::fountain::Encoder::from_reader`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let file = std::io::Cursor::new(vec![7; 1000]);
+    /// let mut encoder = ur::Encoder::from_reader(file, 1000, 100, "bytes").unwrap();
+    /// assert_eq!(encoder.fragment_count(), 10);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If a zero message length or maximum fragment length is passed, or reading
+    /// fails or ends before the message length, an error will be returned.
+    pub fn from_reader<T: Into<String>>(
+        reader: impl std::io::Read,
+        message_length: usize,
+        max_fragment_length: usize,
+        ur_type: T,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            fountain: crate::fountain::Encoder::from_reader(
+                reader,
+                message_length,
+                max_fragment_length,
+            )?,
+            ur_type: ur_type.into(),
+        })
+    }
+
     /// Returns the URI corresponding to next fountain part.
     ///
     /// # Examples
@@ -92,26 +192,48 @@ impl Encoder {
     ///
     /// If serialization fails an error will be returned.
     pub fn next_part(&mut self) -> anyhow::Result<String> {
-        let part = self.fountain.next_part();
-        let body = crate::bytewords::encode(&part.cbor()?, &crate::bytewords::Style::Minimal);
-        Ok(encode_ur(&[self.ur_type.clone(), part.sequence_id(), body]))
+        self.fountain.next_part().to_ur_string(&self.ur_type)
     }
 
-    /// Returns the current count of already emitted parts.
+    /// Returns the current count of already emitted parts, which is the
+    /// sequence number of the last emitted part.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut encoder = ur::Encoder::new("data".as_bytes(), 5, "bytes").unwrap();
-    /// assert_eq!(encoder.current_index(), 0);
+    /// assert_eq!(encoder.current_sequence(), 0);
     /// encoder.next_part().unwrap();
-    /// assert_eq!(encoder.current_index(), 1);
+    /// assert_eq!(encoder.current_sequence(), 1);
     /// ```
     #[must_use]
-    pub fn current_index(&self) -> usize {
+    pub fn current_sequence(&self) -> usize {
         self.fountain.current_sequence()
     }
 
+    /// Returns the current count of already emitted parts.
+    #[deprecated(note = "use `current_sequence()` instead")]
+    #[must_use]
+    pub fn current_index(&self) -> usize {
+        self.current_sequence()
+    }
+
+    /// Returns the [`crate::message_id_words`] of the encoded message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+    /// assert_eq!(
+    ///     encoder.message_id_words(),
+    ///     ur::message_id_words("Ten chars!".as_bytes())
+    /// );
+    /// ```
+    #[must_use]
+    pub fn message_id_words(&self) -> [&'static str; 4] {
+        crate::message_id_words(&self.fountain.message())
+    }
+
     /// Returns the number of segments the original message has been split up into.
     ///
     /// # Examples
@@ -128,12 +250,289 @@ impl Encoder {
 
 /// An enum used to indicate whether a UR is single- or
 /// multip-part. See e.g. [`decode`] where it is returned.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Kind {
     SinglePart,
     MultiPart,
 }
 
+/// A uniform resource which has been split up into its components,
+/// but whose `bytewords` payload has not been decoded yet.
+///
+/// # Examples
+///
+/// ```
+/// let ur = ur::ur::UR::parse("ur:bytes/1-2/iehsjyhspmwfwfia").unwrap();
+/// assert_eq!(ur.ur_type(), "bytes");
+/// assert_eq!(ur.kind(), ur::ur::Kind::MultiPart);
+/// assert_eq!(ur.sequence(), Some(1));
+/// assert_eq!(ur.sequence_count(), Some(2));
+/// assert_eq!(ur.decode_payload().unwrap(), "data".as_bytes());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UR<'a> {
+    ur_type: &'a str,
+    indices: Option<(usize, usize)>,
+    payload: &'a str,
+}
+
+impl<'a> UR<'a> {
+    /// Parses a single URI (either single- or multi-part) into its components.
+    ///
+    /// # Examples
+    ///
+    /// See the [`UR`] documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a [`ParseError`] for invalid inputs, for example
+    /// an invalid scheme different from "ur" or an invalid number
+    /// of "/" separators.
+    pub fn parse(value: &'a str) -> anyhow::Result<Self> {
+        let strip_scheme = value.strip_prefix("ur:").ok_or(ParseError::InvalidScheme)?;
+        let (ur_type, strip_type) = strip_scheme
+            .split_once('/')
+            .ok_or(ParseError::MissingType)?;
+        if !ur_type
+            .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '-')
+            .is_empty()
+        {
+            return Err(ParseError::InvalidType.into());
+        }
+        match strip_type.rsplit_once('/') {
+            None => Ok(Self {
+                ur_type,
+                indices: None,
+                payload: strip_type,
+            }),
+            Some((indices, payload)) => {
+                let (idx, idx_total) = indices.split_once('-').ok_or(ParseError::InvalidIndices)?;
+                let (idx, idx_total) = idx
+                    .parse::<u16>()
+                    .ok()
+                    .zip(idx_total.parse::<u16>().ok())
+                    .ok_or(ParseError::InvalidIndices)?;
+                Ok(Self {
+                    ur_type,
+                    indices: Some((idx.into(), idx_total.into())),
+                    payload,
+                })
+            }
+        }
+    }
+
+    /// Returns the type of the uniform resource, e.g. `bytes`.
+    #[must_use]
+    pub fn ur_type(&self) -> &'a str {
+        self.ur_type
+    }
+
+    /// Returns whether the uniform resource is single- or multi-part.
+    #[must_use]
+    pub fn kind(&self) -> Kind {
+        if self.indices.is_some() {
+            Kind::MultiPart
+        } else {
+            Kind::SinglePart
+        }
+    }
+
+    /// Returns the sequence number of a multi-part uniform resource,
+    /// `None` for single-part ones.
+    ///
+    /// The header sequence number equals the [`crate::fountain::Part::sequence`]
+    /// of the carried part.
+    #[must_use]
+    pub fn sequence(&self) -> Option<usize> {
+        self.indices.map(|(sequence, _)| sequence)
+    }
+
+    /// Returns the sequence count of a multi-part uniform resource,
+    /// `None` for single-part ones.
+    ///
+    /// The header sequence count normally equals the
+    /// [`crate::fountain::Part::sequence_count`] of the carried part, the number
+    /// of fragments of the message, but may differ for some producers, see
+    /// [`HeaderPolicy`].
+    #[must_use]
+    pub fn sequence_count(&self) -> Option<usize> {
+        self.indices.map(|(_, sequence_count)| sequence_count)
+    }
+
+    /// Returns the still `bytewords`-encoded payload.
+    #[must_use]
+    pub fn payload(&self) -> &'a str {
+        self.payload
+    }
+
+    /// Decodes the minimal `bytewords` payload.
+    ///
+    /// # Errors
+    ///
+    /// If the payload is not a well-formed minimal `bytewords` string,
+    /// an error will be returned.
+    pub fn decode_payload(&self) -> Result<Vec<u8>, crate::bytewords::Error> {
+        crate::bytewords::decode(self.payload, &crate::bytewords::Style::Minimal)
+    }
+
+    /// Reads the header of the fountain part carried by a multi-part uniform
+    /// resource, e.g. to route the part to the decoder of its message.
+    ///
+    /// Only the leading words of the payload are decoded, at most 30 bytes
+    /// whatever the length of the fragment. Hence the header is not verified
+    /// against the checksum of the payload, and a part may still fail to
+    /// decode with [`crate::fountain::Part::from_ur`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+    /// let part = encoder.next_part().unwrap();
+    /// let header = ur::ur::UR::parse(&part).unwrap().peek_part_header().unwrap();
+    /// assert_eq!(header.sequence, 1);
+    /// assert_eq!(header.metadata.message_length, 10);
+    /// assert_eq!(header.metadata.fragment_length, 4);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the uniform resource is single-part, or the leading words of its
+    /// payload are not a valid `bytewords` encoding of a part header, an error
+    /// will be returned.
+    pub fn peek_part_header(&self) -> anyhow::Result<crate::fountain::PartHeader> {
+        anyhow::ensure!(self.kind() == Kind::MultiPart, "expected multi-part ur");
+        let cbor = crate::bytewords::decode_minimal_head(
+            self.payload,
+            crate::fountain::MAX_HEADER_LENGTH,
+        )?;
+        crate::fountain::PartHeader::from_cbor_head(&cbor)
+    }
+}
+
+/// The errors that can be returned when parsing a URI, see [`UR::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The URI doesn't start with the `ur:` scheme.
+    InvalidScheme,
+    /// The URI has no `/` separating the type from the payload.
+    MissingType,
+    /// The type contains characters other than letters, digits and hyphens.
+    InvalidType,
+    /// The `<seq>-<seq-count>` header of a multi-part URI is malformed.
+    InvalidIndices,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidScheme => write!(f, "Invalid scheme"),
+            ParseError::MissingType => write!(f, "No type specified"),
+            ParseError::InvalidType => write!(f, "Type contains invalid characters"),
+            ParseError::InvalidIndices => {
+                write!(f, "Invalid indices, must match `<idx>-<len>`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let error = ur::ur::UR::parse("ur:bytes").unwrap_err();
+    /// assert_eq!(
+    ///     error.downcast_ref::<ur::ur::ParseError>().unwrap().code(),
+    ///     ur::ErrorCode(302)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(match self {
+            ParseError::InvalidScheme => 301,
+            ParseError::MissingType => 302,
+            ParseError::InvalidType => 303,
+            ParseError::InvalidIndices => 304,
+        })
+    }
+}
+
+/// The error yielded by [`parse_many`] for text between the URIs which
+/// doesn't start with the `ur:` scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StrayText {
+    /// The skipped text, its whitespace-separated words joined by single spaces.
+    pub text: String,
+}
+
+impl std::fmt::Display for StrayText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "skipped stray text: {}", self.text)
+    }
+}
+
+impl std::error::Error for StrayText {}
+
+impl StrayText {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::ur::StrayText;
+    /// assert_eq!(StrayText { text: "text".into() }.code(), ur::ErrorCode(306));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(306)
+    }
+}
+
+/// Parses several URIs separated by whitespace, as concatenated in a
+/// clipboard or an NFC record, see [`UR::parse`].
+///
+/// Every word starting with the `ur:` scheme is parsed on its own, so a
+/// malformed URI doesn't affect the others. The words between the URIs are
+/// skipped, each run of them yielding a [`StrayText`] error.
+///
+/// # Examples
+///
+/// ```
+/// use ur::ur::StrayText;
+/// let input = "ur:bytes/iehsjyhspmwfwfia\nur:bytes/1-2/iehsjyhspmwfwfia and more";
+/// let mut urs = ur::parse_many(input);
+/// assert_eq!(urs.next().unwrap().unwrap().kind(), ur::ur::Kind::SinglePart);
+/// assert_eq!(urs.next().unwrap().unwrap().kind(), ur::ur::Kind::MultiPart);
+/// let e = urs.next().unwrap().unwrap_err();
+/// assert_eq!(
+///     e.downcast_ref::<StrayText>(),
+///     Some(&StrayText {
+///         text: "and more".into()
+///     })
+/// );
+/// assert!(urs.next().is_none());
+/// ```
+pub fn parse_many(input: &str) -> impl Iterator<Item = anyhow::Result<UR<'_>>> {
+    let mut words = input.split_whitespace().peekable();
+    std::iter::from_fn(move || {
+        let word = words.next()?;
+        if word.starts_with("ur:") {
+            return Some(UR::parse(word));
+        }
+        let mut skipped = vec![word];
+        while let Some(word) = words.next_if(|word| !word.starts_with("ur:")) {
+            skipped.push(word);
+        }
+        Some(Err(StrayText {
+            text: skipped.join(" "),
+        }
+        .into()))
+    })
+}
+
 /// Decodes a single URI (either single- or multi-part)
 /// into a tuple consisting of the [`Kind`] and the data
 /// payload.
@@ -157,92 +556,714 @@ pub enum Kind {
 /// an invalid scheme different from "ur" or an invalid number
 /// of "/" separators.
 pub fn decode(value: &str) -> anyhow::Result<(Kind, Vec<u8>)> {
-    let strip_scheme = value.strip_prefix("ur:").context("Invalid scheme")?;
-    let (type_, strip_type) = strip_scheme.split_once('/').context("No type specified")?;
-    anyhow::ensure!(
-        type_
-            .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '-')
-            .is_empty(),
-        "Type contains invalid characters"
-    );
-    match strip_type.rsplit_once('/') {
-        None => Ok((
-            Kind::SinglePart,
-            crate::bytewords::decode(strip_type, &crate::bytewords::Style::Minimal)?,
-        )),
-        Some((indices, payload)) => {
-            let (idx, idx_total) = indices.split_once('-').context("Invalid indices")?;
-            anyhow::ensure!(
-                idx.parse::<u16>().is_ok() && idx_total.parse::<u16>().is_ok(),
-                "Invalid indices, must match `<idx>-<len>`"
-            );
-            Ok((
-                Kind::MultiPart,
-                crate::bytewords::decode(payload, &crate::bytewords::Style::Minimal)?,
-            ))
-        }
+    let ur = UR::parse(value)?;
+    Ok((ur.kind(), ur.decode_payload()?))
+}
+
+/// Determines how a [`Decoder`] treats a URI whose `<seq>-<seq-count>` header
+/// disagrees with the sequence count of the fountain part it carries.
+///
+/// Some producers have been observed to increment the sequence count in the
+/// header on later passes over the message, while the part itself is unchanged,
+/// hence the default tolerates such a drift.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeaderPolicy {
+    /// A header sequence count differing from the one of the part is ignored
+    /// and counted in [`Statistics::header_count_drift`]. The sequence number
+    /// must still match.
+    #[default]
+    Tolerant,
+    /// The header must match the sequence number and sequence count of the part.
+    Strict,
+}
+
+/// Anomalies observed by a [`Decoder`] which didn't prevent decoding, and
+/// the memory it has used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Statistics {
+    /// The number of parts whose header sequence count differed from the
+    /// sequence count of the part, see [`HeaderPolicy::Tolerant`].
+    pub header_count_drift: usize,
+    /// The number of parts rejected for exceeding the maximum accepted degree,
+    /// see [`Decoder::with_max_accepted_degree`].
+    pub rejected_degree: usize,
+    /// The number of parts which solved an already solved fragment with
+    /// different data, see [`crate::fountain::FragmentConflict`].
+    pub fragment_conflicts: usize,
+    /// The number of parts received before completion whose fragments had
+    /// all been received or solved before, e.g. on an earlier pass over the message.
+    pub duplicate_parts: usize,
+    /// The number of parts rejected for a type which is not allowed,
+    /// see [`Decoder::with_allowed_types`].
+    pub rejected_type: usize,
+    /// The estimated peak of bytes held by the decoder, see
+    /// [`Decoder::peak_memory_estimate`].
+    pub peak_memory_estimate: usize,
+}
+
+/// The error returned by a [`Decoder`] for a part of a type which is not
+/// allowed, see [`Decoder::with_allowed_types`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeNotAllowed {
+    /// The type of the part.
+    pub ur_type: String,
+}
+
+impl std::fmt::Display for TypeNotAllowed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ur type {} is not allowed", self.ur_type)
+    }
+}
+
+impl std::error::Error for TypeNotAllowed {}
+
+impl TypeNotAllowed {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::ur::TypeNotAllowed;
+    /// let error = TypeNotAllowed { ur_type: "bytes".into() };
+    /// assert_eq!(error.code(), ur::ErrorCode(305));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(305)
+    }
+}
+
+/// The error returned by a [`Decoder`] for a URI whose `<seq>-<seq-count>`
+/// header disagrees with the fountain part it carries, see [`HeaderPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaderMismatch {
+    /// The sequence number and sequence count of the header.
+    pub header: (usize, usize),
+    /// The sequence number and sequence count of the part.
+    pub part: (usize, usize),
+}
+
+impl std::fmt::Display for HeaderMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "header {}-{} does not match part {}-{}",
+            self.header.0, self.header.1, self.part.0, self.part.1
+        )
+    }
+}
+
+impl std::error::Error for HeaderMismatch {}
+
+impl HeaderMismatch {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::ur::HeaderMismatch;
+    /// let error = HeaderMismatch { header: (1, 3), part: (1, 2) };
+    /// assert_eq!(error.code(), ur::ErrorCode(307));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(307)
+    }
+}
+
+/// The error returned by a [`Decoder`] for a part of another type than the
+/// previously received parts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeMismatch {
+    /// The type of the previously received parts.
+    pub expected: String,
+    /// The type of the part.
+    pub got: String,
+}
+
+impl std::fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected ur type {}, got {}", self.expected, self.got)
+    }
+}
+
+impl std::error::Error for TypeMismatch {}
+
+impl TypeMismatch {
+    /// Returns the stable [`crate::ErrorCode`] of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ur::ur::TypeMismatch;
+    /// let error = TypeMismatch { expected: "bytes".into(), got: "other".into() };
+    /// assert_eq!(error.code(), ur::ErrorCode(308));
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode(308)
     }
 }
 
 /// A uniform resource decoder able to receive URIs that encode a fountain part.
 ///
+/// Like the underlying [`crate::fountain::Decoder`], a decoder can be cloned to
+/// fork its state, at the cost of copying the parts received so far.
+///
 /// # Examples
 ///
 /// See the [`crate::ur`] module documentation for an example.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Decoder {
     fountain: crate::fountain::Decoder,
+    ur_type: Option<String>,
+    allowed_types: Option<Vec<String>>,
+    header_policy: HeaderPolicy,
+    statistics: Statistics,
 }
 
 impl Decoder {
-    /// Receives a URI representing a CBOR and `bytewords`-encoded fountain part
-    /// into the decoder.
+    /// Sets the [`SequencePolicy`] of the underlying fountain decoder.
     ///
     /// # Examples
     ///
-    /// See the [`crate::ur`] module documentation for an example.
+    /// ```
+    /// use ur::fountain::SequencePolicy;
+    /// let mut encoder = ur::Encoder::new(&"data".repeat(10).as_bytes(), 3, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default().with_sequence_policy(SequencePolicy::Strict);
+    /// let first = encoder.next_part().unwrap();
+    /// decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// assert!(decoder.receive(&first).is_err());
+    /// ```
     ///
-    /// # Errors
+    /// [`SequencePolicy`]: crate::fountain::SequencePolicy
+    #[must_use]
+    pub fn with_sequence_policy(mut self, policy: crate::fountain::SequencePolicy) -> Self {
+        self.fountain = self.fountain.with_sequence_policy(policy);
+        self
+    }
+
+    /// Sets the maximum number of fragments a received part may combine,
+    /// see [`fountain::Decoder::with_max_accepted_degree`].
     ///
-    /// This function may error along all the necessary decoding steps:
-    ///  - The string may not be a well-formed URI according to the uniform resource scheme
-    ///  - The URI payload may not be a well-formed `bytewords` string
-    ///  - The decoded byte payload may not be valid CBOR
-    ///  - The CBOR-encoded fountain part may be inconsistent with previously received ones
+    /// Rejected parts are counted in the [`Statistics`].
     ///
-    /// In all these cases, an error will be returned.
-    pub fn receive(&mut self, value: &str) -> anyhow::Result<()> {
-        let (kind, decoded) = decode(value)?;
-        anyhow::ensure!(kind == Kind::MultiPart, "Tried to receive a single-part ur");
-        self.fountain
-            .receive(crate::fountain::Part::from_cbor(decoded.as_slice())?)?;
-        Ok(())
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new(&[0; 100], 10, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default().with_max_accepted_degree(3);
+    /// let mut rejected = 0;
+    /// while !decoder.complete() {
+    ///     if decoder.receive(&encoder.next_part().unwrap()).is_err() {
+    ///         rejected += 1;
+    ///     }
+    /// }
+    /// assert_eq!(decoder.statistics().rejected_degree, rejected);
+    /// ```
+    ///
+    /// [`fountain::Decoder::with_max_accepted_degree`]: crate::fountain::Decoder::with_max_accepted_degree
+    #[must_use]
+    pub fn with_max_accepted_degree(mut self, max_accepted_degree: usize) -> Self {
+        self.fountain = self.fountain.with_max_accepted_degree(max_accepted_degree);
+        self
     }
 
-    /// Returns whether the decoder is complete and hence the message available.
+    /// Sets the [`HeaderPolicy`] of the decoder.
     ///
     /// # Examples
     ///
-    /// See the [`crate::ur`] module documentation for an example.
+    /// ```
+    /// use ur::ur::HeaderPolicy;
+    /// let mut encoder = ur::Encoder::new("data".as_bytes(), 3, "bytes").unwrap();
+    /// let part = encoder.next_part().unwrap().replace("/1-2/", "/1-3/");
+    ///
+    /// let mut decoder = ur::Decoder::default();
+    /// decoder.receive(&part).unwrap();
+    /// assert_eq!(decoder.statistics().header_count_drift, 1);
+    ///
+    /// let mut decoder = ur::Decoder::default().with_header_policy(HeaderPolicy::Strict);
+    /// assert!(decoder.receive(&part).is_err());
+    /// ```
     #[must_use]
-    pub fn complete(&self) -> bool {
-        self.fountain.complete()
+    pub fn with_header_policy(mut self, policy: HeaderPolicy) -> Self {
+        self.header_policy = policy;
+        self
     }
 
-    /// If [`complete`], returns the decoded message, `None` otherwise.
-    ///
-    /// # Errors
+    /// Restricts the types of the parts the decoder accepts to the given ones,
+    /// compared ignoring ASCII case.
     ///
-    /// If an inconsistent internal state detected, an error will be returned.
+    /// A part of any other type is rejected with a [`TypeNotAllowed`] error
+    /// before it is decoded, and counted in the [`Statistics`]. By default,
+    /// parts of any type are accepted.
     ///
     /// # Examples
     ///
-    /// See the [`crate::ur`] module documentation for an example.
+    /// ```
+    /// use ur::ur::TypeNotAllowed;
+    /// let mut decoder = ur::Decoder::default().with_allowed_types(&["crypto-psbt"]);
+    /// let mut encoder = ur::Encoder::new("data".as_bytes(), 3, "bytes").unwrap();
+    /// let e = decoder.receive(&encoder.next_part().unwrap()).unwrap_err();
+    /// assert!(e.is::<TypeNotAllowed>());
+    /// assert_eq!(decoder.statistics().rejected_type, 1);
     ///
-    /// [`complete`]: Decoder::complete
-    pub fn message(&self) -> anyhow::Result<Option<Vec<u8>>> {
-        self.fountain.message()
-    }
+    /// let mut encoder = ur::Encoder::new("data".as_bytes(), 3, "crypto-psbt").unwrap();
+    /// decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// ```
+    #[must_use]
+    pub fn with_allowed_types(mut self, ur_types: &[&str]) -> Self {
+        self.allowed_types = Some(
+            ur_types
+                .iter()
+                .map(|ur_type| ur_type.to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns the anomalies observed so far and the estimated peak memory.
+    #[must_use]
+    pub fn statistics(&self) -> Statistics {
+        Statistics {
+            peak_memory_estimate: self.peak_memory_estimate(),
+            ..self.statistics
+        }
+    }
+
+    /// Returns an estimate of the most bytes the decoder has held allocated
+    /// at once, see [`fountain::Decoder::peak_memory_estimate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new(&[0; 1000], 100, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// assert!(decoder.peak_memory_estimate() > 1000);
+    /// assert_eq!(
+    ///     decoder.statistics().peak_memory_estimate,
+    ///     decoder.peak_memory_estimate()
+    /// );
+    /// ```
+    ///
+    /// [`fountain::Decoder::peak_memory_estimate`]: crate::fountain::Decoder::peak_memory_estimate
+    #[must_use]
+    pub fn peak_memory_estimate(&self) -> usize {
+        self.fountain.peak_memory_estimate()
+    }
+
+    /// Receives a URI representing a CBOR and `bytewords`-encoded fountain part
+    /// into the decoder.
+    ///
+    /// Once complete, the decoder ignores all URIs without checking them,
+    /// whether they belong to the decoded message or another one, and leaves
+    /// its state and [`Statistics`] untouched until it is [`clear`]ed.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::ur`] module documentation for an example.
+    ///
+    /// # Errors
+    ///
+    /// This function may error along all the necessary decoding steps:
+    ///  - The string may not be a well-formed URI according to the uniform resource
+    ///    scheme, see [`ParseError`]
+    ///  - The URI type may not be allowed, see [`Decoder::with_allowed_types`]
+    ///  - The URI payload may not be a well-formed `bytewords` string
+    ///  - The decoded byte payload may not be valid CBOR
+    ///  - The URI header may disagree with the fountain part, subject to the
+    ///    [`HeaderPolicy`], see [`HeaderMismatch`]
+    ///  - The URI type may differ from the one of previously received parts, see
+    ///    [`TypeMismatch`]
+    ///  - The CBOR-encoded fountain part may be inconsistent with previously received ones
+    ///  - The fountain part may exceed the maximum accepted degree
+    ///  - The fountain part may conflict with previously solved fragments
+    ///
+    /// In all these cases, an error will be returned.
+    ///
+    /// [`clear`]: Decoder::clear
+    pub fn receive(&mut self, value: &str) -> anyhow::Result<()> {
+        if self.complete() {
+            return Ok(());
+        }
+        let ur = UR::parse(value)?;
+        // the allow-list and the type of the message compare types ignoring case
+        let ur_type = ur.ur_type().to_ascii_lowercase();
+        if let Some(allowed_types) = &self.allowed_types {
+            if !allowed_types.contains(&ur_type) {
+                self.statistics.rejected_type += 1;
+                return Err(TypeNotAllowed {
+                    ur_type: ur.ur_type().to_string(),
+                }
+                .into());
+            }
+        }
+        let part = crate::fountain::Part::from_ur(&ur)?;
+        self.check_header(&ur, &part)?;
+        if let Some(expected) = &self.ur_type {
+            if *expected != ur_type {
+                return Err(TypeMismatch {
+                    expected: expected.clone(),
+                    got: ur.ur_type().to_string(),
+                }
+                .into());
+            }
+        }
+        match self.fountain.receive(part) {
+            Ok(false) => self.statistics.duplicate_parts += 1,
+            Ok(true) => {}
+            Err(e) => {
+                if e.is::<crate::fountain::DegreeError>() {
+                    self.statistics.rejected_degree += 1;
+                }
+                if e.is::<crate::fountain::FragmentConflict>() {
+                    self.statistics.fragment_conflicts += 1;
+                }
+                return Err(e);
+            }
+        }
+        if self.ur_type.is_none() {
+            self.ur_type = Some(ur_type);
+        }
+        Ok(())
+    }
+
+    fn check_header(&mut self, ur: &UR, part: &crate::fountain::Part) -> anyhow::Result<()> {
+        let (sequence, sequence_count) = ur
+            .sequence()
+            .zip(ur.sequence_count())
+            .context("Tried to receive a single-part ur")?;
+        let drift = sequence_count != part.sequence_count();
+        if sequence != part.sequence() || (drift && self.header_policy == HeaderPolicy::Strict) {
+            return Err(HeaderMismatch {
+                header: (sequence, sequence_count),
+                part: (part.sequence(), part.sequence_count()),
+            }
+            .into());
+        }
+        if drift {
+            self.statistics.header_count_drift += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the decoder is complete and hence the message available.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::ur`] module documentation for an example.
+    #[must_use]
+    pub fn complete(&self) -> bool {
+        self.fountain.complete()
+    }
+
+    /// Discards all received parts and the [`Statistics`] to decode another
+    /// message, keeping the configuration of the decoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut decoder = ur::Decoder::default().with_allowed_types(&["bytes"]);
+    /// for message in ["data", "more data"] {
+    ///     let mut encoder = ur::Encoder::new(message.as_bytes(), 3, "bytes").unwrap();
+    ///     while !decoder.complete() {
+    ///         decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    ///     }
+    ///     assert_eq!(decoder.message().unwrap().as_deref(), Some(message.as_bytes()));
+    ///     decoder.clear();
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        self.fountain.clear();
+        self.ur_type = None;
+        self.statistics = Statistics::default();
+    }
+
+    pub(crate) fn progress(&self) -> (usize, usize) {
+        self.fountain.progress()
+    }
+
+    /// Returns the type of the received parts, `None` if none has been received yet.
+    ///
+    /// Types are compared ignoring ASCII case, and the type is returned in
+    /// lowercase.
+    ///
+    /// Both the type and the [`message`] borrow the decoder immutably,
+    /// so they can be held at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn parse(ur_type: &str, message: &[u8]) -> usize {
+    ///     assert_eq!(ur_type, "crypto-psbt");
+    ///     message.len()
+    /// }
+    /// let mut encoder = ur::Encoder::new(&[0; 100], 10, "crypto-psbt").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// assert_eq!(decoder.ur_type(), None);
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// let message = decoder.message().unwrap().unwrap();
+    /// if let Some(ur_type) = decoder.ur_type() {
+    ///     assert_eq!(parse(ur_type, &message), 100);
+    /// }
+    /// ```
+    ///
+    /// [`message`]: Decoder::message
+    #[must_use]
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// If [`complete`], returns the decoded message, `None` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If the decoded message doesn't match the checksum of the parts, a
+    /// [`crate::fountain::MessageChecksum`] error will be returned. If an
+    /// inconsistent internal state detected, an error will be returned.
+    ///
+    /// # Examples
+    ///
+    /// See the [`crate::ur`] module documentation for an example.
+    ///
+    /// [`complete`]: Decoder::complete
+    pub fn message(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        self.fountain.message()
+    }
+
+    /// If [`complete`], returns the decoded message as a shared buffer, `None`
+    /// otherwise, see [`crate::fountain::Decoder::message_arc`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new(&[7; 100_000], 400, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// let message = decoder.message_arc().unwrap().unwrap();
+    /// let consumers: Vec<_> = (0..3)
+    ///     .map(|_| {
+    ///         let message = message.clone();
+    ///         std::thread::spawn(move || message.len())
+    ///     })
+    ///     .collect();
+    /// for consumer in consumers {
+    ///     assert_eq!(consumer.join().unwrap(), 100_000);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`message`].
+    ///
+    /// [`complete`]: Decoder::complete
+    /// [`message`]: Decoder::message
+    pub fn message_arc(&self) -> anyhow::Result<Option<std::sync::Arc<[u8]>>> {
+        self.fountain.message_arc()
+    }
+
+    /// If [`complete`], returns a reader over the decoded message, `None` otherwise.
+    ///
+    /// The reader reads from the buffer shared with [`message_arc`], which is
+    /// built on the first call, so the message isn't copied for every reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    /// let mut encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// let mut reader = decoder.message_reader().unwrap().unwrap();
+    /// reader.seek(SeekFrom::Start(4)).unwrap();
+    /// let mut rest = String::new();
+    /// reader.read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "chars!");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`message`].
+    ///
+    /// [`complete`]: Decoder::complete
+    /// [`message`]: Decoder::message
+    /// [`message_arc`]: Decoder::message_arc
+    pub fn message_reader(&self) -> anyhow::Result<Option<std::io::Cursor<std::sync::Arc<[u8]>>>> {
+        Ok(self.message_arc()?.map(std::io::Cursor::new))
+    }
+
+    /// If [`complete`], returns the [`crate::message_id_words`] of the decoded
+    /// message, `None` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If an inconsistent internal state detected, an error will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// assert_eq!(decoder.message_id_words().unwrap(), None);
+    /// while !decoder.complete() {
+    ///     decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// }
+    /// assert_eq!(
+    ///     decoder.message_id_words().unwrap(),
+    ///     Some(encoder.message_id_words())
+    /// );
+    /// ```
+    ///
+    /// [`complete`]: Decoder::complete
+    pub fn message_id_words(&self) -> anyhow::Result<Option<[&'static str; 4]>> {
+        Ok(self
+            .message()?
+            .map(|message| crate::message_id_words(&message)))
+    }
+
+    /// Returns the [`crate::ChecksumWords`] of the message being decoded,
+    /// once a part has been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+    /// let mut decoder = ur::Decoder::default();
+    /// assert_eq!(decoder.checksum_words(), None);
+    /// decoder.receive(&encoder.next_part().unwrap()).unwrap();
+    /// assert_eq!(
+    ///     decoder.checksum_words(),
+    ///     Some(ur::ChecksumWords(["work", "warm", "half", "when"]))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn checksum_words(&self) -> Option<crate::ChecksumWords> {
+        self.fountain.checksum().map(crate::ChecksumWords::new)
+    }
+}
+
+/// The decision of a [`Relay`] about a received part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelayDecision {
+    /// The part should be forwarded.
+    Forward,
+    /// The part has been forwarded before.
+    DropDuplicate,
+    /// The part is malformed or belongs to a different message.
+    DropInvalid,
+}
+
+/// Filters the parts of a multi-part transmission for re-broadcasting,
+/// without decoding the message.
+///
+/// The relay sticks to the message of the first valid part and remembers
+/// a bounded number of forwarded parts to drop duplicates. Parts corrupted
+/// in transit fail the `bytewords` checksum of the part and are dropped as
+/// invalid, so they don't reach the downstream decoder.
+///
+/// # Examples
+///
+/// ```
+/// use ur::ur::{RelayDecision, UR};
+/// let mut encoder = ur::Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+/// let mut relay = ur::ur::Relay::default();
+/// let part = encoder.next_part().unwrap();
+/// let ur = UR::parse(&part).unwrap();
+/// assert_eq!(relay.receive_for_relay(&ur), RelayDecision::Forward);
+/// assert_eq!(relay.receive_for_relay(&ur), RelayDecision::DropDuplicate);
+/// let other = UR::parse("ur:bytes/1-3/lpadaxbecyzezowsurgogemhrh").unwrap();
+/// assert_eq!(relay.receive_for_relay(&other), RelayDecision::DropInvalid);
+/// assert_eq!(relay.ur_type(), Some("bytes"));
+/// assert_eq!(relay.metadata().unwrap().message_length, 10);
+/// ```
+pub struct Relay {
+    message: Option<(String, crate::fountain::Metadata)>,
+    seen: std::collections::HashSet<(usize, u32)>,
+    order: std::collections::VecDeque<(usize, u32)>,
+    max_seen: usize,
+}
+
+impl Default for Relay {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl Relay {
+    /// Creates a new [`Relay`] remembering up to `max_seen` forwarded parts.
+    #[must_use]
+    pub fn new(max_seen: usize) -> Self {
+        Self {
+            message: None,
+            seen: std::collections::HashSet::new(),
+            order: std::collections::VecDeque::new(),
+            max_seen,
+        }
+    }
+
+    /// Decides whether a received uniform resource should be forwarded.
+    ///
+    /// # Examples
+    ///
+    /// See the [`Relay`] documentation for an example.
+    pub fn receive_for_relay(&mut self, ur: &UR) -> RelayDecision {
+        self.decide(ur).unwrap_or(RelayDecision::DropInvalid)
+    }
+
+    // Returns `None` if the part is invalid.
+    fn decide(&mut self, ur: &UR) -> Option<RelayDecision> {
+        let part = crate::fountain::Part::from_ur(ur).ok()?;
+        if ur.sequence() != Some(part.sequence())
+            || ur.sequence_count() != Some(part.sequence_count())
+            || part.sequence() == 0
+            || part.sequence_count() == 0
+        {
+            return None;
+        }
+        match &self.message {
+            Some((ur_type, metadata))
+                if ur_type != ur.ur_type() || *metadata != part.metadata() =>
+            {
+                return None;
+            }
+            Some(_) => {}
+            None => self.message = Some((ur.ur_type().to_string(), part.metadata())),
+        }
+        let key = (part.sequence(), crate::crc32().checksum(part.data()));
+        if !self.seen.insert(key) {
+            return Some(RelayDecision::DropDuplicate);
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.max_seen {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        Some(RelayDecision::Forward)
+    }
+
+    /// Returns the type of the relayed message, once known.
+    #[must_use]
+    pub fn ur_type(&self) -> Option<&str> {
+        self.message.as_ref().map(|(ur_type, _)| ur_type.as_str())
+    }
+
+    /// Returns the metadata of the relayed message, once known.
+    #[must_use]
+    pub fn metadata(&self) -> Option<crate::fountain::Metadata> {
+        self.message.as_ref().map(|(_, metadata)| *metadata)
+    }
 }
 
 #[cfg(test)]
@@ -259,13 +1280,61 @@ mod tests {
     #[test]
     fn test_single_part_ur() {
         let ur = make_message_ur(50, "Wolf");
-        let encoded = encode(&ur, "bytes");
+        let encoded = encode_ur("bytes", &ur).unwrap();
         let expected = "ur:bytes/hdeymejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtgwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsdwkbrkch";
         assert_eq!(encoded, expected);
         let decoded = decode(&encoded).unwrap();
         assert_eq!((Kind::SinglePart, ur), decoded);
     }
 
+    #[test]
+    fn test_encode_ur_validation() {
+        assert_eq!(
+            encode_ur("", b"data").unwrap_err().to_string(),
+            "expected non-empty type"
+        );
+        for ur_type in ["by/tes", "bytes!", "BYTES", "by tes"] {
+            assert_eq!(
+                encode_ur(ur_type, b"data").unwrap_err().to_string(),
+                "Type contains invalid characters"
+            );
+        }
+        assert_eq!(
+            encode_ur("bytes", b"").unwrap_err().to_string(),
+            "expected non-empty payload"
+        );
+        assert_eq!(
+            encode_ur("crypto-psbt-2", b"data").unwrap(),
+            "ur:crypto-psbt-2/iehsjyhspmwfwfia"
+        );
+    }
+
+    #[test]
+    fn test_reader_round_trip() {
+        use std::io::Read;
+
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 2_000_000);
+        let mut encoder =
+            Encoder::from_reader(message.as_slice(), message.len(), 20_000, "bytes").unwrap();
+        let mut expected = Encoder::new(&message, 20_000, "bytes").unwrap();
+        let mut decoder = Decoder::default();
+        while !decoder.complete() {
+            let part = encoder.next_part().unwrap();
+            assert_eq!(part, expected.next_part().unwrap());
+            decoder.receive(&part).unwrap();
+        }
+        let mut decoded = Vec::new();
+        let mut reader = decoder.message_reader().unwrap().unwrap();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, message);
+        // readers share the decoded message instead of copying it
+        assert!(std::sync::Arc::ptr_eq(
+            reader.get_ref(),
+            &decoder.message_arc().unwrap().unwrap()
+        ));
+        assert!(Decoder::default().message_reader().unwrap().is_none());
+    }
+
     #[test]
     fn test_ur_encoder() {
         let ur = make_message_ur(256, "Wolf");
@@ -294,11 +1363,33 @@ mod tests {
         ];
         assert_eq!(encoder.fragment_count(), 9);
         for (index, e) in expected.into_iter().enumerate() {
-            assert_eq!(encoder.current_index(), index);
+            assert_eq!(encoder.current_sequence(), index);
             assert_eq!(encoder.next_part().unwrap(), e);
         }
     }
 
+    #[test]
+    fn test_empty_and_single_byte_messages() {
+        assert!(Encoder::new(&[], 10, "bytes")
+            .err()
+            .unwrap()
+            .is::<crate::fountain::EmptyMessage>());
+
+        let mut encoder = Encoder::new(&[7], 10, "bytes").unwrap();
+        let part = encoder.next_part().unwrap();
+        let mut decoder = Decoder::default();
+        decoder.receive(&part).unwrap();
+        assert_eq!(decoder.message().unwrap(), Some(vec![7]));
+
+        // a part claiming an empty message
+        let cbor = hex::decode("850101001a0000000040").unwrap();
+        let payload = crate::bytewords::encode(&cbor, &crate::bytewords::Style::Minimal);
+        let e = Decoder::default()
+            .receive(&format!("ur:bytes/1-1/{payload}"))
+            .unwrap_err();
+        assert!(e.is::<crate::fountain::EmptyMessage>());
+    }
+
     #[test]
     fn test_ur_encoder_decoder_bc_crypto_request() {
         // https://github.com/BlockchainCommons/crypto-commons/blob/67ea252f4a7f295bb347cb046796d5b445b3ad3c/Docs/ur-99-request-response.md#the-seed-request
@@ -328,7 +1419,7 @@ mod tests {
 
         let data = serde_cbor::to_vec(&top_level).unwrap();
 
-        let e = encode(&data, "crypto-request");
+        let e = encode_ur("crypto-request", &data).unwrap();
         let expected = "ur:crypto-request/oeadtpdagdaobncpftlnylfgfgmuztihbawfsgrtflaotaadwkoyadtaaohdhdcxvsdkfgkepezepefrrffmbnnbmdvahnptrdtpbtuyimmemweootjshsmhlunyeslnameyhsdi";
         assert_eq!(expected, e);
 
@@ -349,6 +1440,557 @@ mod tests {
         assert_eq!(decoder.message().unwrap(), Some(ur));
     }
 
+    #[test]
+    fn test_part_ur_round_trip() {
+        let ur = make_message_ur(256, "Wolf");
+        let mut encoder = crate::fountain::Encoder::new(&ur, 30).unwrap();
+        for _ in 0..20 {
+            let part = encoder.next_part();
+            let encoded = part.to_ur_string("bytes").unwrap();
+            let parsed = UR::parse(&encoded).unwrap();
+            assert_eq!(parsed.ur_type(), "bytes");
+            let decoded = crate::fountain::Part::from_ur(&parsed).unwrap();
+            assert_eq!(decoded.to_string(), part.to_string());
+            assert_eq!(decoded.cbor().unwrap(), part.cbor().unwrap());
+        }
+        assert_eq!(
+            crate::fountain::Part::from_ur(&UR::parse("ur:bytes/aeadaolazmjendeoti").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "Tried to receive a single-part ur"
+        );
+    }
+
+    // Compares the emitted parts against a reference implementation such as a
+    // thin command line wrapper around the C++ `bc-ur` library. The binary is
+    // read from the `UR_REFERENCE_ENCODER` environment variable. It receives the
+    // hex-encoded message, the maximum fragment length and the number of parts
+    // as arguments and is expected to print one UR per line.
+    #[test]
+    #[ignore = "requires a reference encoder binary, see `UR_REFERENCE_ENCODER`"]
+    fn test_differential_reference_encoder() {
+        let binary = match std::env::var("UR_REFERENCE_ENCODER") {
+            Ok(binary) => binary,
+            Err(_) => return,
+        };
+        for (length, max_fragment_length) in [(1, 10), (256, 30), (1000, 100), (32767, 1000)] {
+            let message = make_message_ur(length, "Wolf");
+            let count = 100;
+            let output = std::process::Command::new(&binary)
+                .arg(hex::encode(&message))
+                .arg(max_fragment_length.to_string())
+                .arg(count.to_string())
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let expected = String::from_utf8(output.stdout).unwrap();
+            let mut encoder = Encoder::new(&message, max_fragment_length, "bytes").unwrap();
+            let mut decoder = Decoder::default();
+            for line in expected.lines().take(count) {
+                assert_eq!(encoder.next_part().unwrap(), line);
+                decoder.receive(line).unwrap();
+            }
+            assert_eq!(decoder.message().unwrap(), Some(message));
+        }
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let mut encoder = Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        for sequence in 1..=30 {
+            let value = encoder.next_part().unwrap();
+            assert_eq!(encoder.current_sequence(), sequence);
+            let ur = UR::parse(&value).unwrap();
+            let part = crate::fountain::Part::from_ur(&ur).unwrap();
+            assert_eq!(ur.sequence(), Some(sequence));
+            assert_eq!(part.sequence(), sequence);
+            assert_eq!(ur.sequence_count(), Some(encoder.fragment_count()));
+            assert_eq!(part.sequence_count(), encoder.fragment_count());
+            // the first parts of the sequence are the fragments themselves
+            if sequence <= part.sequence_count() {
+                assert_eq!(part.indexes(), vec![sequence - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder_header_count_drift() {
+        // synthetic frames mimicking a producer which increments the header
+        // sequence count on its second pass over the message
+        let frames = [
+            "ur:bytes/1-3/lpadaxchcyvdsbnnlnfdiejpiniyjyinjtiolfsaaerp",
+            "ur:bytes/3-3/lpaxaxchcyvdsbnnlnfdihcxiajlkpjtjyaehhnthfol",
+            "ur:bytes/4-4/lpaaaxchcyvdsbnnlnfdcxjkihjskpihjtiajtahzsis",
+        ];
+        let mut decoder = Decoder::default().with_header_policy(HeaderPolicy::Strict);
+        decoder.receive(frames[0]).unwrap();
+        decoder.receive(frames[1]).unwrap();
+        assert_eq!(
+            decoder.receive(frames[2]).unwrap_err().to_string(),
+            "header 4-4 does not match part 4-3"
+        );
+        assert!(!decoder.complete());
+
+        let mut decoder = Decoder::default();
+        for frame in frames {
+            decoder.receive(frame).unwrap();
+        }
+        assert_eq!(
+            decoder.message().unwrap().as_deref(),
+            Some("drifting sequence count".as_bytes())
+        );
+        assert_eq!(decoder.statistics().header_count_drift, 1);
+
+        // the sequence number must match regardless of the policy
+        let mut decoder = Decoder::default();
+        assert_eq!(
+            decoder
+                .receive("ur:bytes/5-3/lpaaaxchcyvdsbnnlnfdcxjkihjskpihjtiajtahzsis")
+                .unwrap_err()
+                .to_string(),
+            "header 5-3 does not match part 4-3"
+        );
+        assert_eq!(decoder.statistics(), Statistics::default());
+    }
+
+    #[test]
+    fn test_duplicate_parts() {
+        let mut encoder = Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        let parts: Vec<String> = (0..10).map(|_| encoder.next_part().unwrap()).collect();
+        let mut decoder = Decoder::default();
+        // the first pass misses every third part
+        for (index, part) in parts.iter().enumerate() {
+            if index % 3 != 0 {
+                decoder.receive(part).unwrap();
+            }
+        }
+        for part in parts.iter().chain(&parts) {
+            decoder.receive(part).unwrap();
+        }
+        assert_eq!(decoder.message().unwrap(), Some(vec![0; 100]));
+        assert_eq!(decoder.statistics().duplicate_parts, 6);
+    }
+
+    #[test]
+    fn test_reception_order() {
+        let message = crate::xoshiro::test_utils::make_message("Wolf", 500);
+        let mut encoder = Encoder::new(&message, 20, "bytes").unwrap();
+        let mut decoder = Decoder::default();
+        let mut parts = Vec::new();
+        let mut skip = false;
+        while !decoder.complete() {
+            let part = encoder.next_part().unwrap();
+            // skip every other part to rely on the reduction of mixed parts
+            if !skip {
+                decoder.receive(&part).unwrap();
+                parts.push(part);
+            }
+            skip = !skip;
+        }
+        let mut rng = crate::xoshiro::Xoshiro256::from("order");
+        for _ in 0..200 {
+            let mut decoder = Decoder::default();
+            for part in rng.shuffled(parts.clone()) {
+                decoder.receive(&part).unwrap();
+            }
+            assert_eq!(decoder.message().unwrap().as_ref(), Some(&message));
+        }
+    }
+
+    #[test]
+    fn test_relay() {
+        let message = make_message_ur(32767, "Wolf");
+        let mut encoder = Encoder::new(&message, 1000, "bytes").unwrap();
+        let mut other = Encoder::new(&message[1..], 1000, "bytes").unwrap();
+        let mut relay = Relay::new(10);
+        let mut decoder = Decoder::default();
+        let mut forwarded = 0;
+        while !decoder.complete() {
+            let part = encoder.next_part().unwrap();
+            for received in [
+                part.clone(),
+                part.clone(),
+                other.next_part().unwrap(),
+                part.replace("ur:bytes", "ur:other"),
+            ] {
+                let decision = relay.receive_for_relay(&UR::parse(&received).unwrap());
+                if decision == RelayDecision::Forward {
+                    forwarded += 1;
+                    decoder.receive(&received).unwrap();
+                }
+            }
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message.clone()));
+        assert_eq!(forwarded, encoder.current_sequence());
+        assert_eq!(relay.ur_type(), Some("bytes"));
+        assert_eq!(relay.metadata().unwrap().message_length, message.len());
+
+        // a corrupted part is dropped, the downstream decoder completes from the others
+        let mut encoder = Encoder::new(&message, 1000, "bytes").unwrap();
+        let mut relay = Relay::default();
+        let mut decoder = Decoder::default();
+        let first = encoder.next_part().unwrap();
+        let corrupted = first.replacen("/lp", "/lr", 1);
+        assert_ne!(corrupted, first);
+        assert_eq!(
+            relay.receive_for_relay(&UR::parse(&corrupted).unwrap()),
+            RelayDecision::DropInvalid
+        );
+        while !decoder.complete() {
+            let part = encoder.next_part().unwrap();
+            if relay.receive_for_relay(&UR::parse(&part).unwrap()) == RelayDecision::Forward {
+                decoder.receive(&part).unwrap();
+            }
+        }
+        assert_eq!(decoder.message().unwrap(), Some(message.clone()));
+
+        // only a bounded number of parts is remembered
+        let mut relay = Relay::new(1);
+        let mut encoder = Encoder::new(&message, 1000, "bytes").unwrap();
+        let first = encoder.next_part().unwrap();
+        let second = encoder.next_part().unwrap();
+        for part in [&first, &second, &first] {
+            assert_eq!(
+                relay.receive_for_relay(&UR::parse(part).unwrap()),
+                RelayDecision::Forward
+            );
+        }
+        assert_eq!(
+            relay.receive_for_relay(&UR::parse(&first).unwrap()),
+            RelayDecision::DropDuplicate
+        );
+        assert_eq!(
+            relay.receive_for_relay(&UR::parse("ur:bytes/1-2/aeadaolazmjendeoti").unwrap()),
+            RelayDecision::DropInvalid
+        );
+    }
+
+    #[test]
+    fn test_part_checksum() {
+        let mut encoder = Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        let part = encoder.next_part().unwrap();
+        // a misread letter which still forms a valid word
+        let corrupted = part.replacen("/lp", "/lr", 1);
+        let e = Decoder::default().receive(&corrupted).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<crate::bytewords::Error>(),
+            Some(&crate::bytewords::Error::InvalidChecksum)
+        );
+        assert!(!e.is::<crate::fountain::MessageChecksum>());
+    }
+
+    #[test]
+    fn test_decoder_ur_type() {
+        let mut encoder = Encoder::new(&[0; 100], 10, "crypto-psbt").unwrap();
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.ur_type(), None);
+        let part = encoder.next_part().unwrap();
+        decoder.receive(&part).unwrap();
+        assert_eq!(decoder.ur_type(), Some("crypto-psbt"));
+        assert_eq!(
+            decoder
+                .receive(&encoder.next_part().unwrap().replace("crypto-psbt", "bytes"))
+                .unwrap_err()
+                .to_string(),
+            "expected ur type crypto-psbt, got bytes"
+        );
+        // a rejected first part doesn't determine the type
+        let mut decoder = Decoder::default().with_max_accepted_degree(0);
+        assert!(decoder.receive(&part).is_err());
+        assert_eq!(decoder.ur_type(), None);
+    }
+
+    #[test]
+    fn test_allowed_types() {
+        let mut encoder = Encoder::new(&[0; 100], 10, "crypto-psbt").unwrap();
+        let part = encoder.next_part().unwrap();
+        let mut decoder = Decoder::default().with_allowed_types(&["bytes", "Crypto-PSBT"]);
+        decoder.receive(&part).unwrap();
+        assert_eq!(decoder.ur_type(), Some("crypto-psbt"));
+
+        // a disallowed first part leaves the decoder untouched
+        let mut decoder = Decoder::default().with_allowed_types(&["bytes"]);
+        let e = decoder.receive(&part).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<TypeNotAllowed>(),
+            Some(&TypeNotAllowed {
+                ur_type: "crypto-psbt".into()
+            })
+        );
+        assert_eq!(e.to_string(), "ur type crypto-psbt is not allowed");
+        assert_eq!(decoder.ur_type(), None);
+        assert_eq!(decoder.message_id_words().unwrap(), None);
+        assert_eq!(decoder.statistics().rejected_type, 1);
+        // even if the rest of the part is malformed
+        assert!(decoder
+            .receive("ur:crypto-psbt/1-10/x")
+            .unwrap_err()
+            .is::<TypeNotAllowed>());
+        assert_eq!(decoder.statistics().rejected_type, 2);
+
+        let mut bytes = Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        decoder.receive(&bytes.next_part().unwrap()).unwrap();
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        // the case of the type may change between parts
+        let part = bytes.next_part().unwrap().replace("bytes", "BYTES");
+        decoder.receive(&part).unwrap();
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        let mut decoder = Decoder::default();
+        decoder.receive(&part).unwrap();
+        decoder.receive(&bytes.next_part().unwrap()).unwrap();
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        assert!(Decoder::default()
+            .with_allowed_types(&[])
+            .receive(&bytes.next_part().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let first = encode_ur("crypto-hdkey", b"key").unwrap();
+        let second = encode_ur("crypto-address", b"address").unwrap();
+        let parse = |input: &str| -> Vec<Result<String, String>> {
+            parse_many(input)
+                .map(|ur| {
+                    ur.map(|ur| ur.ur_type().to_string())
+                        .map_err(|e| e.to_string())
+                })
+                .collect()
+        };
+        assert_eq!(
+            parse(&format!("{first}\n{second}")),
+            [Ok("crypto-hdkey".into()), Ok("crypto-address".into())]
+        );
+        assert_eq!(
+            parse(&format!("  {first} \r\n {second}\tjunk \n")),
+            [
+                Ok("crypto-hdkey".into()),
+                Ok("crypto-address".into()),
+                Err("skipped stray text: junk".into())
+            ]
+        );
+        assert_eq!(
+            parse(&format!("scan: {first} then\nsome text ur:bytes {second}")),
+            [
+                Err("skipped stray text: scan:".into()),
+                Ok("crypto-hdkey".into()),
+                Err("skipped stray text: then some text".into()),
+                Err("No type specified".into()),
+                Ok("crypto-address".into()),
+            ]
+        );
+        assert!(parse(" \n ").is_empty());
+        // a malformed second uri doesn't affect the first one
+        let mut urs = parse_many("ur:bytes/iehsjyhspmwfwfia\nur:bytes#/iehsjyhspmwfwfia");
+        assert_eq!(
+            urs.next().unwrap().unwrap().decode_payload().unwrap(),
+            b"data"
+        );
+        assert_eq!(
+            urs.next().unwrap().unwrap_err().to_string(),
+            "Type contains invalid characters"
+        );
+        assert!(urs.next().is_none());
+    }
+
+    #[test]
+    fn test_receive_after_completion() {
+        let mut encoder = Encoder::new("Ten chars!".as_bytes(), 4, "bytes").unwrap();
+        let mut decoder = Decoder::default().with_allowed_types(&["bytes"]);
+        while !decoder.complete() {
+            decoder.receive(&encoder.next_part().unwrap()).unwrap();
+        }
+        let statistics = decoder.statistics();
+        let mut other = Encoder::new("Other message".as_bytes(), 4, "bytes").unwrap();
+        let mut other_type = Encoder::new("Ten chars!".as_bytes(), 4, "crypto-psbt").unwrap();
+        for part in [
+            encoder.next_part().unwrap(),
+            encoder.next_part().unwrap().replace("/5-3/", "/5-4/"),
+            other.next_part().unwrap(),
+            other_type.next_part().unwrap(),
+            "ur:bytes/1-3/invalid".into(),
+            "not a ur".into(),
+        ] {
+            decoder.receive(&part).unwrap();
+            assert!(decoder.complete());
+        }
+        assert_eq!(decoder.statistics(), statistics);
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        assert_eq!(
+            decoder.message().unwrap().as_deref(),
+            Some("Ten chars!".as_bytes())
+        );
+
+        decoder.clear();
+        assert!(!decoder.complete());
+        assert_eq!(decoder.ur_type(), None);
+        assert_eq!(decoder.message().unwrap(), None);
+        assert!(decoder
+            .receive(&other_type.next_part().unwrap())
+            .unwrap_err()
+            .is::<TypeNotAllowed>());
+        while !decoder.complete() {
+            decoder.receive(&other.next_part().unwrap()).unwrap();
+        }
+        assert_eq!(
+            decoder.message().unwrap().as_deref(),
+            Some("Other message".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_peek_part_header() {
+        for (message_length, max_fragment_length) in [
+            (1, 1),
+            (10, 4),
+            (1000, 400),
+            (100_000, 400),
+            (70_000, 70_000),
+        ] {
+            let mut encoder =
+                Encoder::new(&vec![7; message_length], max_fragment_length, "bytes").unwrap();
+            for _ in 0..encoder.fragment_count() + 5 {
+                let part = encoder.next_part().unwrap();
+                let ur = UR::parse(&part).unwrap();
+                let full = crate::fountain::Part::from_ur(&ur).unwrap();
+                assert_eq!(
+                    ur.peek_part_header().unwrap(),
+                    crate::fountain::PartHeader {
+                        sequence: full.sequence(),
+                        metadata: full.metadata(),
+                    }
+                );
+            }
+        }
+
+        let part = Encoder::new(&[0; 1000], 400, "bytes")
+            .unwrap()
+            .next_part()
+            .unwrap();
+        let peek = |value: &str| {
+            UR::parse(value)
+                .unwrap()
+                .peek_part_header()
+                .map_err(|e| e.to_string())
+        };
+        // the fragment data and the checksum are not decoded, the header of
+        // the first part takes 14 bytes
+        let (head, _) = part.split_at("ur:bytes/1-3/".len() + 28);
+        assert!(peek(head).is_ok());
+        let (window, rest) = part.split_at("ur:bytes/1-3/".len() + 60);
+        assert!(peek(&format!("{window}zz{}", rest.split_at(2).1)).is_ok());
+        assert_eq!(
+            peek(part.split_at(head.len() - 2).0).unwrap_err(),
+            "truncated part header"
+        );
+        assert_eq!(
+            peek(part.split_at(head.len() - 1).0).unwrap_err(),
+            crate::bytewords::Error::Truncated {
+                missing_chars_at_least: 1
+            }
+            .to_string()
+        );
+        assert_eq!(
+            peek(&part.replacen("/lp", "/zz", 1)).unwrap_err(),
+            crate::bytewords::Error::InvalidWord.to_string()
+        );
+        let forged = |cbor: &[u8]| {
+            let payload = crate::bytewords::encode(cbor, &crate::bytewords::Style::Minimal);
+            peek(&format!("ur:bytes/1-2/{payload}")).unwrap_err()
+        };
+        assert_eq!(forged(&[0xa5]), "invalid top-level item");
+        assert_eq!(forged(&[0x84, 1, 2, 3, 4]), "invalid cbor array length");
+        assert_eq!(forged(&[0x85, 1, 2, 0x20]), "unexpected item at position 2");
+        assert_eq!(
+            forged(&[0x85, 0x1b, 0, 0, 0, 1, 0, 0, 0, 0]),
+            "unexpected item at position 0"
+        );
+        assert_eq!(
+            forged(&[0x85, 1, 2, 3, 4, 0x64]),
+            "unexpected item at position 4"
+        );
+        assert_eq!(
+            UR::parse("ur:bytes/iehsjyhspmwfwfia")
+                .unwrap()
+                .peek_part_header()
+                .unwrap_err()
+                .to_string(),
+            "expected multi-part ur"
+        );
+    }
+
+    #[test]
+    fn test_message_arc() {
+        let message = crate::xoshiro::test_utils::make_message("shared", 10_000);
+        let mut encoder = Encoder::new(&message, 100, "bytes").unwrap();
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.message_arc().unwrap(), None);
+        while !decoder.complete() {
+            decoder.receive(&encoder.next_part().unwrap()).unwrap();
+        }
+        let shared = decoder.message_arc().unwrap().unwrap();
+        assert_eq!(*shared, *message);
+        assert!(std::sync::Arc::ptr_eq(
+            &shared,
+            &decoder.message_arc().unwrap().unwrap()
+        ));
+        assert_eq!(decoder.message().unwrap(), Some(message.clone()));
+        // a fork shares the buffer built before
+        assert!(std::sync::Arc::ptr_eq(
+            &shared,
+            &decoder.clone().message_arc().unwrap().unwrap()
+        ));
+
+        decoder.clear();
+        assert_eq!(decoder.message_arc().unwrap(), None);
+        assert_eq!(*shared, *message);
+        let mut other = Encoder::new(b"other", 100, "bytes").unwrap();
+        decoder.receive(&other.next_part().unwrap()).unwrap();
+        assert_eq!(&*decoder.message_arc().unwrap().unwrap(), b"other");
+        assert_eq!(*shared, *message);
+    }
+
+    #[test]
+    fn test_fork() {
+        let message = crate::xoshiro::test_utils::make_message("fork", 100);
+        let mut encoder = Encoder::new(&message, 10, "bytes").unwrap();
+        let mut decoder = Decoder::default();
+        for _ in 0..5 {
+            decoder.receive(&encoder.next_part().unwrap()).unwrap();
+        }
+        let mut fork = decoder.clone();
+        let mut forked_encoder = encoder.clone();
+        assert_eq!(forked_encoder.current_sequence(), 5);
+
+        // the original receives the remaining fragments
+        for _ in 0..5 {
+            decoder.receive(&encoder.next_part().unwrap()).unwrap();
+        }
+        assert!(decoder.complete());
+        assert!(!fork.complete());
+
+        // the fork misses them, rejects a restarted sender and recovers from mixed parts
+        for _ in 0..5 {
+            forked_encoder.next_part().unwrap();
+        }
+        let mut restarted = Encoder::new(&[0; 100], 10, "bytes").unwrap();
+        assert!(fork.receive(&restarted.next_part().unwrap()).is_err());
+        while !fork.complete() {
+            fork.receive(&forked_encoder.next_part().unwrap()).unwrap();
+        }
+        assert!(forked_encoder.current_sequence() > 10);
+        assert_eq!(encoder.current_sequence(), 10);
+        assert_eq!(decoder.message().unwrap(), Some(message.clone()));
+        assert_eq!(fork.message().unwrap(), Some(message));
+        assert_eq!(
+            decoder.statistics(),
+            Statistics {
+                peak_memory_estimate: decoder.peak_memory_estimate(),
+                ..Statistics::default()
+            }
+        );
+    }
+
     #[test]
     fn test_decoder() {
         assert_eq!(
@@ -48,9 +48,7 @@ impl Xoshiro256 {
         shuffled
     }
 
-    pub(crate) fn choose_degree(&mut self, length: usize) -> u32 {
-        let degree_weights: Vec<f64> = (1..=length).map(|x| 1.0 / x as f64).collect();
-        let mut sampler = crate::sampler::Weighted::new(degree_weights);
+    pub(crate) fn choose_degree(&mut self, sampler: &mut crate::sampler::Weighted) -> u32 {
         sampler.next(self) + 1
     }
 }
@@ -0,0 +1,74 @@
+//! Compares the memory estimate of the fountain decoder with the allocations
+//! it actually makes, counted by a global allocator. This lives in its own
+//! test binary, and only the allocations of the test thread are counted, so
+//! the test harness doesn't interfere.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct Counting;
+
+thread_local! {
+    // the bytes allocated by this thread and not deallocated yet
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+fn count(delta: isize) {
+    // the thread-local is unavailable while the thread exits
+    let _ = LIVE.try_with(|live| live.set(live.get() + delta));
+}
+
+fn live() -> isize {
+    LIVE.with(Cell::get)
+}
+
+#[allow(clippy::cast_possible_wrap)]
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        count(-(layout.size() as isize));
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[test]
+fn test_peak_memory_estimate() {
+    let message: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+    let mut encoder = ur::fountain::Encoder::new(&message, 100).unwrap();
+    // every third part is lost, so that mixed parts are buffered
+    let parts: Vec<_> = (0..3000)
+        .map(|_| encoder.next_part())
+        .enumerate()
+        .filter(|(i, _)| i % 3 != 2)
+        .map(|(_, part)| part)
+        .collect();
+
+    let baseline = live();
+    let mut decoder = ur::fountain::Decoder::default();
+    let mut held = 0;
+    let mut parts = parts.iter();
+    while !decoder.complete() {
+        decoder.receive(parts.next().unwrap().clone()).unwrap();
+        held = held.max(live() - baseline);
+    }
+    let held = usize::try_from(held).unwrap();
+    let estimate = decoder.peak_memory_estimate();
+    assert!(held <= estimate, "held {held}, estimated {estimate}");
+    assert!(estimate <= 2 * held, "held {held}, estimated {estimate}");
+
+    // the shared message is accounted for as well
+    let shared = decoder.message_arc().unwrap().unwrap();
+    assert_eq!(shared.len(), message.len());
+    let held = held.max(usize::try_from(live() - baseline).unwrap());
+    assert!(held <= decoder.peak_memory_estimate());
+
+    decoder.clear();
+    assert_eq!(decoder.peak_memory_estimate(), 0);
+}